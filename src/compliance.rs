@@ -0,0 +1,445 @@
+use std::collections::HashSet;
+
+use crate::fields::parse_number;
+use crate::graph::base_name;
+use crate::{InfEntry, InfSection, RegistryRoot, Span, StringTable, WinInfFile};
+
+/// The driver model an INF file is checked against
+///
+/// Levels are ordered from the least to the most restrictive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComplianceLevel {
+    /// Any INF file Windows can install
+    #[default]
+    Legacy,
+    /// A universal INF file, which installs on every Windows edition
+    Universal,
+    /// A declarative (DCH) driver, which also keeps its files in the driver store
+    Declarative,
+}
+
+impl std::fmt::Display for ComplianceLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComplianceLevel::Legacy => write!(f, "legacy"),
+            ComplianceLevel::Universal => write!(f, "universal"),
+            ComplianceLevel::Declarative => write!(f, "declarative"),
+        }
+    }
+}
+
+/// The DIRIDs universal and declarative drivers may use in `[DestinationDirs]`
+pub const UNIVERSAL_DIRIDS: &[u32] = &[11, 12, 13];
+
+/// Directives universal INF files cannot use
+const UNIVERSAL_FORBIDDEN_DIRECTIVES: &[&str] = &[
+    "LogConfig",
+    "ProfileItems",
+    "UpdateInis",
+    "UpdateIniFields",
+    "Ini2Reg",
+    "RegisterDlls",
+    "UnregisterDlls",
+    "DelFiles",
+    "RenFiles",
+];
+
+/// Companion sections universal INF files cannot have, e.g. `[Inst.NT.CoInstallers]`
+const UNIVERSAL_FORBIDDEN_SECTIONS: &[&str] = &["CoInstallers", "FactDef", "LogConfigOverride"];
+
+/// Sections whose keys are names rather than directives, e.g. `[Strings.0409]`
+const NAMED_ENTRY_SECTIONS: &[&str] = &[
+    "Strings",
+    "DestinationDirs",
+    "SourceDisksNames",
+    "SourceDisksFiles",
+];
+
+/// The key below `HKLM` that declarative drivers may still write to
+const DECLARATIVE_HKLM_KEY: &str = "SYSTEM\\CurrentControlSet\\Services\\";
+
+/// The codes of [`ComplianceViolation`]s, with a description of what they check
+pub(crate) const COMPLIANCE_CODES: &[(&str, &str)] = &[
+    (
+        "disallowed-directive",
+        "universal and declarative drivers do not use legacy directives such as LogConfig",
+    ),
+    (
+        "disallowed-section",
+        "universal and declarative drivers have no co-installer or LogConfigOverride sections",
+    ),
+    (
+        "disallowed-registry-root",
+        "universal drivers only write HKR and HKLM, declarative drivers only HKR",
+    ),
+    (
+        "disallowed-dirid",
+        "universal and declarative drivers only copy files to DIRIDs 11, 12 and 13",
+    ),
+    (
+        "copyfiles-system32",
+        "declarative drivers run their files from the driver store, not from System32",
+    ),
+];
+
+/// A directive or section an INF file cannot have at some [`ComplianceLevel`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplianceViolation {
+    /// The least restrictive level that does not allow the directive
+    pub level: ComplianceLevel,
+    /// A stable, machine readable identifier, e.g. `disallowed-directive`
+    pub code: &'static str,
+    /// The section the directive is in
+    pub section: String,
+    /// The directive, the registry root or the section suffix that is not allowed
+    pub directive: String,
+    /// A human readable description of the violation
+    pub message: String,
+    /// Where the directive is in the parsed content
+    pub span: Option<Span>,
+}
+
+/// The result of checking an INF file against a [`ComplianceLevel`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplianceReport {
+    /// The most restrictive level the INF file complies with
+    pub classification: ComplianceLevel,
+    /// The violations of the level the file was checked against, in file order
+    pub violations: Vec<ComplianceViolation>,
+}
+
+impl ComplianceReport {
+    /// Returns `true` if the INF file complies with the level it was checked against
+    pub fn is_compliant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl WinInfFile {
+    /// Returns every directive and section that is not allowed at some level, in file order
+    ///
+    /// Universal INF files cannot use legacy directives such as `LogConfig`,
+    /// `UpdateIniFields` or `DelFiles`, cannot have `.CoInstallers`, `.FactDef` or
+    /// `.LogConfigOverride` sections, cannot write the `HKCR`, `HKCU` or `HKU` registry
+    /// roots and only copy files to [`UNIVERSAL_DIRIDS`]. Declarative drivers also only
+    /// write `HKR` or their service keys below `HKLM\SYSTEM\CurrentControlSet\Services`,
+    /// and do not copy files to System32.
+    pub fn compliance_violations(&self) -> Vec<ComplianceViolation> {
+        let strings = self.string_table();
+        let mut violations = vec![];
+        let mut registry_sections = HashSet::new();
+
+        for section in &self.sections {
+            let suffix = section.name().rsplit('.').next().unwrap_or_default();
+            if section.name().contains('.') {
+                if let Some(forbidden) = UNIVERSAL_FORBIDDEN_SECTIONS
+                    .iter()
+                    .find(|forbidden| forbidden.eq_ignore_ascii_case(suffix))
+                {
+                    violations.push(ComplianceViolation {
+                        level: ComplianceLevel::Universal,
+                        code: "disallowed-section",
+                        section: section.name().to_string(),
+                        directive: forbidden.to_string(),
+                        message: format!(
+                            "[{}] is not allowed in a universal INF file",
+                            section.name()
+                        ),
+                        span: section.span(),
+                    });
+                }
+            }
+
+            let base = base_name(section.name());
+            if NAMED_ENTRY_SECTIONS
+                .iter()
+                .any(|named| named.eq_ignore_ascii_case(base))
+            {
+                continue;
+            }
+            for (entry, span) in section.entries_with_spans() {
+                let Some(key) = entry.key() else {
+                    continue;
+                };
+                if let Some(forbidden) = UNIVERSAL_FORBIDDEN_DIRECTIVES
+                    .iter()
+                    .find(|forbidden| forbidden.eq_ignore_ascii_case(key))
+                {
+                    violations.push(ComplianceViolation {
+                        level: ComplianceLevel::Universal,
+                        code: "disallowed-directive",
+                        section: section.name().to_string(),
+                        directive: forbidden.to_string(),
+                        message: format!(
+                            "{forbidden} in [{}] is not allowed in a universal INF file",
+                            section.name()
+                        ),
+                        span,
+                    });
+                }
+                if ["AddReg", "DelReg", "BitReg"]
+                    .iter()
+                    .any(|directive| directive.eq_ignore_ascii_case(key))
+                {
+                    let names = entry
+                        .value()
+                        .map(|value| value.fields())
+                        .unwrap_or_default();
+                    for name in names.into_iter().filter(|name| !name.is_empty()) {
+                        let name = strings.expand(name).unwrap_or_else(|_| name.to_string());
+                        registry_sections.insert(name.to_ascii_lowercase());
+                    }
+                }
+                if entry.has_key("CopyFiles") {
+                    self.system32_copies(section, entry, span, &strings, &mut violations);
+                }
+            }
+        }
+
+        // registry sections may come before the sections that reference them
+        for section in &self.sections {
+            if registry_sections.contains(&section.name().to_ascii_lowercase()) {
+                registry_violations(section, &strings, &mut violations);
+            }
+        }
+
+        if let Some(dirs) = self.section("DestinationDirs") {
+            for (entry, span) in dirs.entries_with_spans() {
+                let dirid = entry
+                    .value()
+                    .and_then(|value| value.field(0))
+                    .and_then(|field| strings.expand(field).ok())
+                    .and_then(|field| parse_number(&field));
+                let Some(dirid) = dirid.filter(|dirid| !UNIVERSAL_DIRIDS.contains(dirid)) else {
+                    continue;
+                };
+                let key = entry.key().unwrap_or_default();
+                violations.push(ComplianceViolation {
+                    level: ComplianceLevel::Universal,
+                    code: "disallowed-dirid",
+                    section: dirs.name().to_string(),
+                    directive: key.to_string(),
+                    message: format!(
+                        "DIRID {dirid} for {key} is not allowed in a universal INF file"
+                    ),
+                    span,
+                });
+            }
+        }
+
+        violations.sort_by_key(|violation| violation.span.map(|span| span.start.offset));
+        violations
+    }
+
+    /// Adds a violation for each file list of a `CopyFiles` directive that goes to System32
+    fn system32_copies(
+        &self,
+        section: &InfSection,
+        entry: &InfEntry,
+        span: Option<Span>,
+        strings: &StringTable,
+        violations: &mut Vec<ComplianceViolation>,
+    ) {
+        let names = entry
+            .value()
+            .map(|value| value.fields())
+            .unwrap_or_default();
+        for name in names.into_iter().filter(|name| !name.is_empty()) {
+            let list = name.strip_prefix('@').is_none().then_some(name);
+            let Ok(destination) = self.destination_dir_with(list, strings) else {
+                continue;
+            };
+            if destination.dirid == 11 {
+                violations.push(ComplianceViolation {
+                    level: ComplianceLevel::Declarative,
+                    code: "copyfiles-system32",
+                    section: section.name().to_string(),
+                    directive: "CopyFiles".to_string(),
+                    message: format!(
+                        "CopyFiles in [{}] copies {name} to System32 (DIRID 11)",
+                        section.name()
+                    ),
+                    span,
+                });
+            }
+        }
+    }
+
+    /// Classifies the INF file and lists the violations of a level
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{ComplianceLevel, WinInfFile};
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file
+    ///     .parse_str(concat!(
+    ///         "[DefaultInstall]\n",
+    ///         "AddReg = Reg\n",
+    ///         "[DefaultInstall.CoInstallers]\n",
+    ///         "[Reg]\n",
+    ///         "HKLM,Software\\Contoso,Value,,1\n",
+    ///     ))
+    ///     .unwrap();
+    /// let report = inf_file.check_compliance(ComplianceLevel::Declarative);
+    /// assert_eq!(report.classification, ComplianceLevel::Legacy);
+    /// let found: Vec<_> = report
+    ///     .violations
+    ///     .iter()
+    ///     .map(|v| (v.level, v.directive.as_str()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     found,
+    ///     [
+    ///         (ComplianceLevel::Universal, "CoInstallers"),
+    ///         (ComplianceLevel::Declarative, "HKLM"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn check_compliance(&self, level: ComplianceLevel) -> ComplianceReport {
+        let violations = self.compliance_violations();
+        let classification = match violations.iter().map(|v| v.level).min() {
+            None => ComplianceLevel::Declarative,
+            Some(ComplianceLevel::Declarative) => ComplianceLevel::Universal,
+            Some(_) => ComplianceLevel::Legacy,
+        };
+        ComplianceReport {
+            classification,
+            violations: violations
+                .into_iter()
+                .filter(|violation| violation.level <= level)
+                .collect(),
+        }
+    }
+}
+
+/// Adds a violation for each line of a registry section whose root is not allowed
+fn registry_violations(
+    section: &InfSection,
+    strings: &StringTable,
+    violations: &mut Vec<ComplianceViolation>,
+) {
+    for (entry, span) in section.entries_with_spans() {
+        let Some(value) = entry.value() else {
+            continue;
+        };
+        let fields = value.fields();
+        let expand = |idx: usize| {
+            let field = fields.get(idx).copied().unwrap_or_default();
+            strings.expand(field).unwrap_or_else(|_| field.to_string())
+        };
+        let Ok(root) = expand(0).parse::<RegistryRoot>() else {
+            continue;
+        };
+        let level = match root {
+            RegistryRoot::Relative => continue,
+            RegistryRoot::LocalMachine => {
+                let subkey = expand(1);
+                let is_service_key = subkey
+                    .get(..DECLARATIVE_HKLM_KEY.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(DECLARATIVE_HKLM_KEY));
+                if is_service_key {
+                    continue;
+                }
+                ComplianceLevel::Declarative
+            }
+            _ => ComplianceLevel::Universal,
+        };
+        violations.push(ComplianceViolation {
+            level,
+            code: "disallowed-registry-root",
+            section: section.name().to_string(),
+            directive: root.to_string(),
+            message: format!(
+                "{root} in [{}] is not allowed in a {level} INF file",
+                section.name()
+            ),
+            span,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compliance_levels() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Reg]\n",
+                "HKR,,A,,1\n",
+                "HKLM,SYSTEM\\CurrentControlSet\\Services\\svc\\Parameters,B,,1\n",
+                "[Inst]\n",
+                "AddReg = Reg\n",
+                "CopyFiles = Files\n",
+                "[DestinationDirs]\n",
+                "Files = 13\n",
+            ))
+            .unwrap();
+        let report = inf_file.check_compliance(ComplianceLevel::Declarative);
+        assert_eq!(report.classification, ComplianceLevel::Declarative);
+        assert!(report.is_compliant());
+
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Reg]\n",
+                "HKCU,Software\\Contoso,C,,1\n",
+                "[Inst]\n",
+                "AddReg = Reg\n",
+                "CopyFiles = Files\n",
+                "UpdateIniFields = Ini\n",
+                "[DestinationDirs]\n",
+                "Files = 11\n",
+            ))
+            .unwrap();
+        let violations = inf_file.compliance_violations();
+        let found: Vec<_> = violations.iter().map(|v| (v.level, v.code)).collect();
+        assert!(found.contains(&(ComplianceLevel::Universal, "disallowed-registry-root")));
+        assert!(found.contains(&(ComplianceLevel::Universal, "disallowed-directive")));
+        assert!(found.contains(&(ComplianceLevel::Declarative, "copyfiles-system32")));
+        assert_eq!(
+            inf_file
+                .check_compliance(ComplianceLevel::Universal)
+                .classification,
+            ComplianceLevel::Legacy
+        );
+        assert!(
+            inf_file
+                .check_compliance(ComplianceLevel::Legacy)
+                .is_compliant()
+        );
+    }
+
+    #[test]
+    fn test_compliance_named_entries() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Inst]\n",
+                "CopyFiles = Files\n",
+                "[DestinationDirs]\n",
+                "Files = 13\n",
+                "DelFiles = 13\n",
+                "[SourceDisksFiles]\n",
+                "RenFiles = 1\n",
+                "[Strings]\n",
+                "LogConfig = \"Log configuration\"\n",
+                "[Strings.0407]\n",
+                "ProfileItems = \"Profile\"\n",
+            ))
+            .unwrap();
+        assert_eq!(inf_file.compliance_violations(), []);
+
+        inf_file.parse_str("[Inst.Ini]\nDelFiles = Old\n").unwrap();
+        let violations = inf_file.compliance_violations();
+        let found: Vec<_> = violations
+            .iter()
+            .map(|v| (v.code, v.section.as_str()))
+            .collect();
+        assert_eq!(found, [("disallowed-directive", "Inst.Ini")]);
+    }
+}
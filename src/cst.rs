@@ -0,0 +1,554 @@
+use std::fmt;
+
+use crate::fields::{find_key_separator, split_fields};
+use crate::{InfEncoding, WinInfFile, WinInfFileError};
+
+/// Errors that can occur while reading or editing an [`InfDocument`]
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentError {
+    /// The content is not valid UTF-8, or not valid UTF-16LE after a UTF-16LE byte order mark
+    #[error("Invalid {0:?} content")]
+    InvalidEncoding(InfEncoding),
+    /// The content is not a valid INF file
+    #[error("Failed to parse INF content: {0}")]
+    Parse(#[from] WinInfFileError),
+    /// The section to edit does not exist
+    #[error("Missing section: {0}")]
+    MissingSection(String),
+    /// The entry to edit does not exist
+    #[error("Missing {key} entry in [{section}]")]
+    MissingEntry {
+        /// The section that was searched
+        section: String,
+        /// The key that was not found
+        key: String,
+    },
+}
+
+/// The line terminator of a [`SyntaxLine`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// `\r\n`
+    CrLf,
+    /// `\n`
+    Lf,
+    /// The last line of a file that does not end with a line break
+    None,
+}
+
+impl LineEnding {
+    /// Returns the terminator text
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Lf => "\n",
+            LineEnding::None => "",
+        }
+    }
+}
+
+/// What a [`SyntaxLine`] holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    /// An empty or whitespace-only line
+    Blank,
+    /// A line holding only a `;` comment
+    Comment,
+    /// Text before the first section header, which Windows ignores
+    Stray,
+    /// A `[name]` section header
+    SectionHeader(String),
+    /// The first line of an entry, with the key of a `key = value` entry
+    Entry(Option<String>),
+    /// A line carrying on an entry that ended with a continuation `\`
+    Continuation,
+}
+
+/// A line of an [`InfDocument`], as written in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxLine {
+    text: String,
+    ending: LineEnding,
+    kind: LineKind,
+}
+
+impl SyntaxLine {
+    /// Returns the text of the line, without its terminator
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the terminator of the line
+    pub fn ending(&self) -> LineEnding {
+        self.ending
+    }
+
+    /// Returns what the line holds
+    pub fn kind(&self) -> &LineKind {
+        &self.kind
+    }
+
+    /// Returns the byte range of the value in the line, without a trailing comment or
+    /// the whitespace around it
+    fn value_range(&self) -> (usize, usize) {
+        let start = match (&self.kind, find_key_separator(&self.text)) {
+            (LineKind::Entry(Some(_)), Some(idx)) => idx + 1,
+            _ => 0,
+        };
+        let start = start + (self.text[start..].len() - self.text[start..].trim_start().len());
+        let mut in_quotes = false;
+        let mut end = self.text.len();
+        for (idx, c) in self.text[start..].char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ';' if !in_quotes => {
+                    end = start + idx;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        (start, start + self.text[start..end].trim_end().len())
+    }
+}
+
+/// A lossless syntax tree of an INF file
+///
+/// Unlike [`WinInfFile`], a document keeps comments, blank lines, spacing, line endings,
+/// the encoding and the byte order mark, so [`InfDocument::to_bytes`] reproduces the
+/// parsed content byte for byte. Edits only touch the lines they change.
+///
+/// # Examples
+///
+/// ```
+/// use inf_rs::InfDocument;
+///
+/// let content = b"[Version] \r\n; driver version\r\nDriverVer = 01/01/2024,1.0.0.0 ; bump me\r\n";
+/// let mut document = InfDocument::parse(content).unwrap();
+/// assert_eq!(document.to_bytes(), content);
+///
+/// document
+///     .set_value("Version", "DriverVer", "02/01/2024,1.0.1.0")
+///     .unwrap();
+/// assert_eq!(
+///     document.to_string(),
+///     "[Version] \r\n; driver version\r\nDriverVer = 02/01/2024,1.0.1.0 ; bump me\r\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfDocument {
+    encoding: InfEncoding,
+    bom: bool,
+    lines: Vec<SyntaxLine>,
+}
+
+impl InfDocument {
+    /// Parses INF content, detecting the encoding from its byte order mark as
+    /// [`WinInfFile::parse_bytes`] does
+    pub fn parse(bytes: &[u8]) -> Result<Self, DocumentError> {
+        let (encoding, bom, text) = if let Some(rest) = bytes.strip_prefix(&[0xff, 0xfe]) {
+            if rest.len() % 2 != 0 {
+                return Err(DocumentError::InvalidEncoding(InfEncoding::Utf16Le));
+            }
+            let units = rest
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]));
+            let text = char::decode_utf16(units)
+                .collect::<Result<String, _>>()
+                .map_err(|_| DocumentError::InvalidEncoding(InfEncoding::Utf16Le))?;
+            (InfEncoding::Utf16Le, true, text)
+        } else {
+            let (bom, rest) = match bytes.strip_prefix(&[0xef, 0xbb, 0xbf]) {
+                Some(rest) => (true, rest),
+                None => (false, bytes),
+            };
+            let text = std::str::from_utf8(rest)
+                .map_err(|_| DocumentError::InvalidEncoding(InfEncoding::Utf8))?;
+            (InfEncoding::Utf8, bom, text.to_string())
+        };
+
+        // the regular parser decides what is valid, so both views always agree
+        WinInfFile::default().parse_str(&text)?;
+
+        let mut document = InfDocument {
+            encoding,
+            bom,
+            lines: vec![],
+        };
+        let mut rest = text.as_str();
+        while !rest.is_empty() {
+            let (line, ending, next) = match rest.find('\n') {
+                Some(idx) if rest[..idx].ends_with('\r') => {
+                    (&rest[..idx - 1], LineEnding::CrLf, &rest[idx + 1..])
+                }
+                Some(idx) => (&rest[..idx], LineEnding::Lf, &rest[idx + 1..]),
+                None => (rest, LineEnding::None, ""),
+            };
+            document.lines.push(SyntaxLine {
+                text: line.to_string(),
+                ending,
+                kind: LineKind::Blank,
+            });
+            rest = next;
+        }
+        document.classify();
+        Ok(document)
+    }
+
+    /// Works out the kind of every line, following the rules of the section reader
+    fn classify(&mut self) {
+        let mut in_section = false;
+        let mut continued = false;
+        for line in &mut self.lines {
+            let text = line.text.trim();
+            line.kind = if text.starts_with(';') {
+                LineKind::Comment
+            } else if text.is_empty() {
+                // empty lines are skipped, but a whitespace-only line ends a continued entry
+                if !line.text.is_empty() {
+                    continued = false;
+                }
+                LineKind::Blank
+            } else if text.starts_with('[') && text.ends_with(']') {
+                continued = false;
+                in_section = true;
+                LineKind::SectionHeader(text[1..text.len() - 1].to_string())
+            } else if !in_section {
+                LineKind::Stray
+            } else {
+                let (kind, value) = match (continued, find_key_separator(text)) {
+                    (true, _) => (LineKind::Continuation, text),
+                    (false, Some(idx)) => (
+                        LineKind::Entry(Some(text[..idx].trim().to_string())),
+                        &text[idx + 1..],
+                    ),
+                    (false, None) => (LineKind::Entry(None), text),
+                };
+                continued = split_fields(value, &mut vec![]).unwrap_or(false);
+                kind
+            };
+        }
+    }
+
+    /// Returns the encoding of the document
+    pub fn encoding(&self) -> InfEncoding {
+        self.encoding
+    }
+
+    /// Returns `true` if the document starts with a byte order mark
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// Returns the lines of the document
+    pub fn lines(&self) -> &[SyntaxLine] {
+        &self.lines
+    }
+
+    /// Returns the encoded content, including the byte order mark if the document had one
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let text = self.to_string();
+        match (self.encoding, self.bom) {
+            (InfEncoding::Utf8, false) => text.into_bytes(),
+            (InfEncoding::Utf8, true) => [0xef, 0xbb, 0xbf]
+                .into_iter()
+                .chain(text.into_bytes())
+                .collect(),
+            (InfEncoding::Utf16Le, _) => [0xfeff]
+                .into_iter()
+                .chain(text.encode_utf16())
+                .flat_map(u16::to_le_bytes)
+                .collect(),
+        }
+    }
+
+    /// Parses the document into a [`WinInfFile`]
+    pub fn to_inf_file(&self) -> Result<WinInfFile, DocumentError> {
+        let mut inf_file = WinInfFile::default();
+        inf_file.parse_str(&self.to_string())?;
+        Ok(inf_file)
+    }
+
+    /// Returns the positions of the lines of a section, header included
+    ///
+    /// A section that appears several times yields the lines of every occurrence.
+    fn section_lines(&self, section: &str) -> Vec<usize> {
+        let mut lines = vec![];
+        let mut in_section = false;
+        for (idx, line) in self.lines.iter().enumerate() {
+            if let LineKind::SectionHeader(name) = &line.kind {
+                in_section = name.eq_ignore_ascii_case(section);
+            }
+            if in_section {
+                lines.push(idx);
+            }
+        }
+        lines
+    }
+
+    /// Returns the first line and the last continuation line of the entry with `key`
+    fn find_entry(&self, section: &str, key: &str) -> Result<(usize, usize), DocumentError> {
+        let lines = self.section_lines(section);
+        if lines.is_empty() {
+            return Err(DocumentError::MissingSection(section.to_string()));
+        }
+        let first = lines
+            .iter()
+            .copied()
+            .find(|idx| matches!(&self.lines[*idx].kind, LineKind::Entry(Some(k)) if k.eq_ignore_ascii_case(key)))
+            .ok_or_else(|| DocumentError::MissingEntry {
+                section: section.to_string(),
+                key: key.to_string(),
+            })?;
+        let mut last = first;
+        for idx in first + 1..self.lines.len() {
+            match self.lines[idx].kind {
+                LineKind::Continuation => last = idx,
+                LineKind::Comment => {}
+                _ => break,
+            }
+        }
+        Ok((first, last))
+    }
+
+    /// The line ending used for new lines, the one of the first line
+    fn line_ending(&self) -> LineEnding {
+        self.lines
+            .first()
+            .map(|line| line.ending)
+            .filter(|ending| *ending != LineEnding::None)
+            .unwrap_or(LineEnding::CrLf)
+    }
+
+    /// Replaces the value of the first entry with `key` in a section, ignoring case
+    ///
+    /// `value` is written as it is, so it must be quoted as INF values are, for example
+    /// with the `Display` implementation of [`crate::InfValue`]. The key, the spacing
+    /// around `=` and a trailing comment are kept. An entry continued over several lines
+    /// is replaced by a single line.
+    pub fn set_value(
+        &mut self,
+        section: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), DocumentError> {
+        let (first, last) = self.find_entry(section, key)?;
+        let (start, _) = self.lines[first].value_range();
+        let (_, end) = self.lines[last].value_range();
+        let text = format!(
+            "{}{value}{}",
+            &self.lines[first].text[..start],
+            &self.lines[last].text[end..]
+        );
+        let ending = self.lines[last].ending;
+        self.lines.splice(
+            first..=last,
+            [SyntaxLine {
+                text,
+                ending,
+                kind: LineKind::Blank,
+            }],
+        );
+        self.classify();
+        Ok(())
+    }
+
+    /// Adds an entry line, such as `%Device% = Install, PCI\VEN_1234`, to a section
+    ///
+    /// The line is placed after the last entry of the last occurrence of the section, so
+    /// blank lines and comments before the next section stay where they are.
+    pub fn insert_entry(&mut self, section: &str, line: &str) -> Result<(), DocumentError> {
+        let lines = self.section_lines(section);
+        let Some(header) = lines.first().copied() else {
+            return Err(DocumentError::MissingSection(section.to_string()));
+        };
+        // the header is not blank, so this finds a line of the last occurrence
+        let after = lines
+            .iter()
+            .copied()
+            .rfind(|idx| !matches!(self.lines[*idx].kind, LineKind::Blank | LineKind::Comment))
+            .unwrap_or(header);
+
+        // only the last line can lack a line break, the new line then takes its place
+        let mut ending = self.line_ending();
+        if self.lines[after].ending == LineEnding::None {
+            self.lines[after].ending = ending;
+            ending = LineEnding::None;
+        }
+        self.lines.insert(
+            after + 1,
+            SyntaxLine {
+                text: line.to_string(),
+                ending,
+                kind: LineKind::Blank,
+            },
+        );
+        self.classify();
+        Ok(())
+    }
+
+    /// Removes the first entry with `key` in a section, including its continuation lines
+    pub fn remove_entry(&mut self, section: &str, key: &str) -> Result<(), DocumentError> {
+        let (first, last) = self.find_entry(section, key)?;
+        let ending = self.lines[last].ending;
+        self.lines.drain(first..=last);
+        // the line before takes over the missing line break at the end of the file
+        if ending == LineEnding::None && first == self.lines.len() && first > 0 {
+            self.lines[first - 1].ending = LineEnding::None;
+        }
+        self.classify();
+        Ok(())
+    }
+
+    /// Appends a new, empty section at the end of the document
+    pub fn append_section(&mut self, name: &str) {
+        let ending = self.line_ending();
+        let had_ending = self
+            .lines
+            .last()
+            .is_none_or(|line| line.ending != LineEnding::None);
+        if let Some(last) = self.lines.last_mut() {
+            last.ending = ending;
+            self.lines.push(SyntaxLine {
+                text: String::new(),
+                ending,
+                kind: LineKind::Blank,
+            });
+        }
+        self.lines.push(SyntaxLine {
+            text: format!("[{name}]"),
+            ending: if had_ending { ending } else { LineEnding::None },
+            kind: LineKind::Blank,
+        });
+        self.classify();
+    }
+}
+
+/// Writes the text of the document, as it would be before encoding
+impl fmt::Display for InfDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            write!(f, "{}{}", line.text, line.ending.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = concat!(
+        "; header comment\r\n",
+        "[Version]\r\n",
+        "Signature   = \"$WINDOWS NT$\"   ; quoted\r\n",
+        "DriverVer=01/01/2024,1.0.0.0\r\n",
+        "\r\n",
+        "[Models]\r\n",
+        "%Dev% = Inst, ID\\1, \\\r\n",
+        "    ; comment inside\r\n",
+        "    ID\\2 ; tail\r\n",
+        "\r\n",
+        "; trailing comment\r\n",
+        "[Strings]\n",
+        "Dev = \"Device; name\"",
+    );
+
+    #[test]
+    fn test_document_round_trip() {
+        let document = InfDocument::parse(CONTENT.as_bytes()).unwrap();
+        assert_eq!(document.to_bytes(), CONTENT.as_bytes());
+        let kinds = document
+            .lines()
+            .iter()
+            .map(SyntaxLine::kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds[0], &LineKind::Comment);
+        assert_eq!(kinds[1], &LineKind::SectionHeader("Version".to_string()));
+        assert_eq!(kinds[2], &LineKind::Entry(Some("Signature".to_string())));
+        assert_eq!(kinds[7], &LineKind::Comment);
+        assert_eq!(kinds[8], &LineKind::Continuation);
+        assert_eq!(document.lines()[12].ending(), LineEnding::None);
+
+        let utf16 = [0xff, 0xfe]
+            .into_iter()
+            .chain(CONTENT.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect::<Vec<_>>();
+        let document = InfDocument::parse(&utf16).unwrap();
+        assert_eq!(document.encoding(), InfEncoding::Utf16Le);
+        assert_eq!(document.to_bytes(), utf16);
+
+        let bom = [0xef, 0xbb, 0xbf]
+            .into_iter()
+            .chain(CONTENT.bytes())
+            .collect::<Vec<_>>();
+        let document = InfDocument::parse(&bom).unwrap();
+        assert!(document.has_bom());
+        assert_eq!(document.to_bytes(), bom);
+    }
+
+    #[test]
+    fn test_document_edits() {
+        let mut document = InfDocument::parse(CONTENT.as_bytes()).unwrap();
+        document
+            .set_value("version", "signature", "\"$Windows NT$\"")
+            .unwrap();
+        document
+            .set_value("Models", "%Dev%", "Inst, ID\\3")
+            .unwrap();
+        document.insert_entry("Version", "Class = Media").unwrap();
+        document.insert_entry("Strings", "Other = \"x\"").unwrap();
+        document.remove_entry("Version", "DriverVer").unwrap();
+        assert_eq!(
+            document.to_string(),
+            concat!(
+                "; header comment\r\n",
+                "[Version]\r\n",
+                "Signature   = \"$Windows NT$\"   ; quoted\r\n",
+                "Class = Media\r\n",
+                "\r\n",
+                "[Models]\r\n",
+                "%Dev% = Inst, ID\\3 ; tail\r\n",
+                "\r\n",
+                "; trailing comment\r\n",
+                "[Strings]\n",
+                "Dev = \"Device; name\"\r\n",
+                "Other = \"x\"",
+            )
+        );
+
+        let inf_file = document.to_inf_file().unwrap();
+        let models = inf_file.section("Models").unwrap();
+        assert_eq!(models.get("%Dev%").unwrap().fields(), vec!["Inst", "ID\\3"]);
+
+        document.append_section("New");
+        assert!(document.to_string().ends_with("Other = \"x\"\r\n\r\n[New]"));
+        document.insert_entry("New", "Key = 1").unwrap();
+        assert!(document.to_string().ends_with("[New]\r\nKey = 1"));
+    }
+
+    #[test]
+    fn test_document_errors() {
+        assert!(matches!(
+            InfDocument::parse(&[0xff, 0xfe, 0x00]),
+            Err(DocumentError::InvalidEncoding(InfEncoding::Utf16Le))
+        ));
+        assert!(matches!(
+            InfDocument::parse(&[b'[', 0xc3]),
+            Err(DocumentError::InvalidEncoding(InfEncoding::Utf8))
+        ));
+        assert!(matches!(
+            InfDocument::parse(b"[A]\nKey = \"open\n"),
+            Err(DocumentError::Parse(_))
+        ));
+        let mut document = InfDocument::parse(b"[A]\nKey = 1\n").unwrap();
+        assert!(matches!(
+            document.set_value("B", "Key", "2"),
+            Err(DocumentError::MissingSection(_))
+        ));
+        assert!(matches!(
+            document.remove_entry("A", "Other"),
+            Err(DocumentError::MissingEntry { .. })
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{InfEntry, InfSection, ManufacturerError, Platform, StringTable, WinInfFile};
+
+/// An entry of a models section
+///
+/// `device-description=install-section-name[,hw-id][,compatible-id...]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelEntry {
+    /// The device description, with `%strkey%` tokens expanded
+    pub device_description: String,
+    /// The name of the install section, without decoration
+    pub install_section: String,
+    /// The hardware ID, if any
+    pub hardware_id: Option<String>,
+    /// The compatible IDs
+    pub compatible_ids: Vec<String>,
+}
+
+impl ModelEntry {
+    fn from_entry(entry: &InfEntry, strings: &StringTable) -> Result<Self, ManufacturerError> {
+        let invalid = || ManufacturerError::InvalidModelEntry(format!("{entry:?}"));
+        let InfEntry::KeyValue(description, Some(value)) = entry else {
+            return Err(invalid());
+        };
+        let fields = value.fields();
+        let Some((install_section, ids)) = fields.split_first() else {
+            return Err(invalid());
+        };
+        if install_section.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut ids = ids.iter().map(|id| id.to_string());
+        Ok(ModelEntry {
+            device_description: strings.expand(description)?,
+            install_section: install_section.to_string(),
+            hardware_id: ids.next().filter(|id| !id.is_empty()),
+            compatible_ids: ids.filter(|id| !id.is_empty()).collect(),
+        })
+    }
+
+    /// Returns `true` if the entry lists the ID as hardware or compatible ID
+    ///
+    /// IDs are compared ignoring case.
+    pub fn matches_id(&self, id: &str) -> bool {
+        self.hardware_id
+            .iter()
+            .chain(&self.compatible_ids)
+            .any(|i| i.eq_ignore_ascii_case(id))
+    }
+}
+
+/// A models entry that matches a device ID
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceMatch<'a> {
+    /// The name of the manufacturer, with `%strkey%` tokens expanded
+    pub manufacturer: String,
+    /// The models section the entry is in
+    pub models_section: &'a InfSection,
+    /// The matching models entry
+    pub model: ModelEntry,
+    /// `true` if the ID matched one of the compatible IDs of the entry
+    pub is_compatible_id: bool,
+    /// The DDInstall section for the platform, `None` if the INF file lacks it
+    pub install_section: Option<&'a InfSection>,
+}
+
+impl WinInfFile {
+    /// Returns the entries of the models sections Setup would use on the platform
+    pub fn model_entries(
+        &self,
+        platform: &Platform,
+    ) -> Result<Vec<(&InfSection, ModelEntry)>, ManufacturerError> {
+        let strings = self.string_table();
+        let mut entries = vec![];
+        for models in self.models_sections(platform)? {
+            for entry in models.section.entries() {
+                entries.push((models.section, ModelEntry::from_entry(entry, &strings)?));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Returns the models entries that install a device with the given ID on the platform
+    ///
+    /// The ID is looked up among hardware and compatible IDs, ignoring case. Matches
+    /// on a hardware ID come before matches on a compatible ID, otherwise entries are
+    /// in file order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Arch, Platform, WinInfFile};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/sampledisplay.inf")).unwrap();
+    /// let matches = inf_file
+    ///     .find_device("pci\\cc_0300", &Platform::new(Arch::Amd64, 10, 0))
+    ///     .unwrap();
+    /// assert_eq!(matches[0].models_section.name(), "Standard.NTamd64");
+    /// assert_eq!(matches[0].install_section.unwrap().name(), "KDODSamp_Inst");
+    /// ```
+    pub fn find_device(
+        &self,
+        id: &str,
+        platform: &Platform,
+    ) -> Result<Vec<DeviceMatch<'_>>, ManufacturerError> {
+        let strings = self.string_table();
+        let mut matches = vec![];
+        for models in self.models_sections(platform)? {
+            for entry in models.section.entries() {
+                let model = ModelEntry::from_entry(entry, &strings)?;
+                if !model.matches_id(id) {
+                    continue;
+                }
+                let is_compatible_id = !model
+                    .hardware_id
+                    .as_deref()
+                    .is_some_and(|hw_id| hw_id.eq_ignore_ascii_case(id));
+                matches.push(DeviceMatch {
+                    manufacturer: models.manufacturer.name.clone(),
+                    models_section: models.section,
+                    install_section: self.install_section(&model.install_section, platform),
+                    model,
+                    is_compatible_id,
+                });
+            }
+        }
+        matches.sort_by_key(|m| m.is_compatible_id);
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Arch;
+
+    #[test]
+    fn test_find_device_compatible_ids() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Manufacturer]\n%Mfg%=Models,NTamd64\n",
+                "[Models.NTamd64]\n",
+                "%Generic%=Generic_Inst,,PCI\\CC_0300\n",
+                "%Specific%=Specific_Inst,PCI\\VEN_1234&DEV_0001,PCI\\CC_0300\n",
+                "[Generic_Inst.NT]\n[Specific_Inst]\n[Specific_Inst.NTamd64]\n",
+                "[Strings]\nMfg=Contoso\nGeneric=Generic display\nSpecific=Specific display\n",
+            ))
+            .unwrap();
+        let platform = Platform::new(Arch::Amd64, 10, 0);
+
+        let matches = inf_file.find_device("PCI\\CC_0300", &platform).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|m| m.is_compatible_id));
+        assert_eq!(matches[0].model.device_description, "Generic display");
+        assert_eq!(matches[0].model.hardware_id, None);
+        assert_eq!(
+            matches[0].install_section.unwrap().name(),
+            "Generic_Inst.NT"
+        );
+        assert_eq!(matches[0].manufacturer, "Contoso");
+
+        let matches = inf_file
+            .find_device("pci\\ven_1234&dev_0001", &platform)
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(!matches[0].is_compatible_id);
+        assert_eq!(
+            matches[0].install_section.unwrap().name(),
+            "Specific_Inst.NTamd64"
+        );
+
+        assert!(
+            inf_file
+                .find_device("PCI\\CC_0300", &Platform::new(Arch::Arm64, 10, 0))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+use crate::Span;
+
+/// How serious a [`Diagnostic`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Informational note, the INF file is still valid
+    Info,
+    /// Something Windows accepts but that is likely a mistake
+    Warning,
+    /// Something Windows rejects or misinterprets
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A message about the contents of an INF file that does not stop parsing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// A stable, machine readable identifier, e.g. `duplicate-section`
+    pub code: &'static str,
+    /// A human readable description of the problem
+    pub message: String,
+    /// Where the problem is in the parsed content, if it is tied to a place
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic that is not tied to a place in the file
+    pub fn new(severity: Severity, code: &'static str, message: String) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message,
+            span: None,
+        }
+    }
+
+    /// Ties the diagnostic to a place in the file
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = self.span {
+            write!(f, "{span}: ")?;
+        }
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use crate::DestinationDir;
+use crate::strings::is_dirid_token;
+
+/// Errors that can occur while rendering DIRID paths
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DiridError {
+    /// The DIRID is neither a system-defined nor a custom DIRID
+    #[error("Unknown DIRID: {0}")]
+    UnknownDirid(u32),
+}
+
+/// A system-defined DIRID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dirid {
+    /// The numeric DIRID, e.g. `12`
+    pub dirid: u32,
+    /// A short name for the directory, e.g. `Drivers`
+    pub name: &'static str,
+    /// The directory on a default installation, relative to the system drive
+    pub path: &'static str,
+}
+
+const fn dirid(dirid: u32, name: &'static str, path: &'static str) -> Dirid {
+    Dirid { dirid, name, path }
+}
+
+/// The system-defined DIRIDs
+///
+/// The DIRID 13 path is the root of the driver store; each package is copied to its own
+/// folder below it.
+pub const SYSTEM_DIRIDS: &[Dirid] = &[
+    dirid(10, "Windows", "Windows"),
+    dirid(11, "System32", "Windows\\System32"),
+    dirid(12, "Drivers", "Windows\\System32\\drivers"),
+    dirid(
+        13,
+        "DriverStore",
+        "Windows\\System32\\DriverStore\\FileRepository",
+    ),
+    dirid(17, "Inf", "Windows\\INF"),
+    dirid(18, "Help", "Windows\\Help"),
+    dirid(20, "Fonts", "Windows\\Fonts"),
+    dirid(21, "Viewers", "Windows\\System32\\viewers"),
+    dirid(23, "Color", "Windows\\System32\\spool\\drivers\\color"),
+    dirid(24, "SystemDisk", ""),
+    dirid(25, "Shared", "Windows"),
+    dirid(30, "BootDisk", ""),
+    dirid(50, "System", "Windows\\System"),
+    dirid(51, "Spool", "Windows\\System32\\spool"),
+    dirid(52, "SpoolDrivers", "Windows\\System32\\spool\\drivers"),
+    dirid(53, "UserProfile", "Users\\Default"),
+    dirid(54, "BootLoader", ""),
+    dirid(55, "PrintProcessors", "Windows\\System32\\spool\\prtprocs"),
+    dirid(
+        16406,
+        "CommonStartMenu",
+        "ProgramData\\Microsoft\\Windows\\Start Menu",
+    ),
+    dirid(
+        16407,
+        "CommonPrograms",
+        "ProgramData\\Microsoft\\Windows\\Start Menu\\Programs",
+    ),
+    dirid(
+        16408,
+        "CommonStartup",
+        "ProgramData\\Microsoft\\Windows\\Start Menu\\Programs\\Startup",
+    ),
+    dirid(16409, "CommonDesktop", "Users\\Public\\Desktop"),
+    dirid(16415, "CommonFavorites", "Users\\Public\\Favorites"),
+    dirid(16419, "CommonAppData", "ProgramData"),
+    dirid(16420, "Windows", "Windows"),
+    dirid(16421, "System", "Windows\\System32"),
+    dirid(16422, "ProgramFiles", "Program Files"),
+    dirid(16425, "SystemX86", "Windows\\SysWOW64"),
+    dirid(16426, "ProgramFilesX86", "Program Files (x86)"),
+    dirid(16427, "CommonFiles", "Program Files\\Common Files"),
+    dirid(16428, "CommonFilesX86", "Program Files (x86)\\Common Files"),
+    dirid(
+        16429,
+        "CommonTemplates",
+        "ProgramData\\Microsoft\\Windows\\Templates",
+    ),
+    dirid(16430, "CommonDocuments", "Users\\Public\\Documents"),
+];
+
+/// How [`DiridTable::render`] writes directories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathStyle {
+    /// Directory names in angle brackets, e.g. `<Drivers>\x.sys`
+    ///
+    /// Angle brackets cannot appear in Windows paths, so the names cannot be confused with
+    /// directories.
+    Symbolic,
+    /// Paths below the root of the table, e.g. `C:\Windows\System32\drivers\x.sys`
+    Concrete,
+}
+
+/// Resolves DIRIDs to directories
+///
+/// The table knows the system-defined DIRIDs in [`SYSTEM_DIRIDS`]. Custom DIRIDs, or
+/// different paths for system-defined ones, can be added with [`DiridTable::insert`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiridTable {
+    root: String,
+    custom: HashMap<u32, String>,
+}
+
+impl Default for DiridTable {
+    fn default() -> Self {
+        Self::new("C:")
+    }
+}
+
+impl DiridTable {
+    /// Creates a table whose concrete paths are below `root`, e.g. `C:` or `D:\Image`
+    pub fn new(root: impl Into<String>) -> Self {
+        let mut root = root.into();
+        while root.ends_with('\\') {
+            root.pop();
+        }
+        DiridTable {
+            root,
+            custom: HashMap::new(),
+        }
+    }
+
+    /// Sets the directory of a DIRID, replacing any system-defined path
+    ///
+    /// The path is used as it is for both path styles.
+    pub fn insert(&mut self, dirid: u32, path: impl Into<String>) -> Option<String> {
+        self.custom.insert(dirid, path.into())
+    }
+
+    /// Returns the system-defined DIRID entry
+    pub fn system(dirid: u32) -> Option<&'static Dirid> {
+        SYSTEM_DIRIDS.iter().find(|d| d.dirid == dirid)
+    }
+
+    /// Returns the directory of a DIRID in the given style
+    pub fn directory(&self, dirid: u32, style: PathStyle) -> Result<String, DiridError> {
+        if let Some(path) = self.custom.get(&dirid) {
+            return Ok(path.clone());
+        }
+        let system = Self::system(dirid).ok_or(DiridError::UnknownDirid(dirid))?;
+        Ok(match style {
+            PathStyle::Symbolic => format!("<{}>", system.name),
+            PathStyle::Concrete if system.path.is_empty() => self.root.clone(),
+            PathStyle::Concrete => format!("{}\\{}", self.root, system.path),
+        })
+    }
+
+    /// Replaces the `%dirid%` tokens in a value with directories
+    ///
+    /// The value should already have its `%strkey%` tokens expanded, as done by
+    /// [`crate::WinInfFile::expand`]; other `%` signs are kept as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{DiridTable, PathStyle};
+    ///
+    /// let dirids = DiridTable::default();
+    /// assert_eq!(
+    ///     dirids.render("%12%\\x.sys", PathStyle::Symbolic).unwrap(),
+    ///     "<Drivers>\\x.sys"
+    /// );
+    /// assert_eq!(
+    ///     dirids.render("%12%\\x.sys", PathStyle::Concrete).unwrap(),
+    ///     "C:\\Windows\\System32\\drivers\\x.sys"
+    /// );
+    /// ```
+    pub fn render(&self, value: &str, style: PathStyle) -> Result<String, DiridError> {
+        let mut rendered = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find('%') {
+            rendered.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let token = after
+                .find('%')
+                .filter(|end| is_dirid_token(&after[..*end]))
+                .and_then(|end| Some((end, after[..end].parse().ok()?)));
+            match token {
+                Some((end, dirid)) => {
+                    rendered.push_str(&self.directory(dirid, style)?);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    rendered.push('%');
+                    rest = after;
+                }
+            }
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+
+    /// Returns the directory of a `[DestinationDirs]` entry
+    pub fn render_destination(
+        &self,
+        dir: &DestinationDir,
+        style: PathStyle,
+    ) -> Result<String, DiridError> {
+        let directory = self.directory(dir.dirid, style)?;
+        Ok(match &dir.subdir {
+            Some(subdir) => format!("{directory}\\{subdir}"),
+            None => directory,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_styles() {
+        let dirids = DiridTable::new("D:\\Image\\");
+        assert_eq!(
+            dirids
+                .render("%13%\\AudioCodec.sys", PathStyle::Concrete)
+                .unwrap(),
+            "D:\\Image\\Windows\\System32\\DriverStore\\FileRepository\\AudioCodec.sys"
+        );
+        assert_eq!(
+            dirids
+                .render("%16422%\\Contoso", PathStyle::Symbolic)
+                .unwrap(),
+            "<ProgramFiles>\\Contoso"
+        );
+        assert_eq!(
+            dirids.render("%24%", PathStyle::Concrete).unwrap(),
+            "D:\\Image"
+        );
+        assert_eq!(
+            dirids
+                .render("%SystemRoot%\\%11%\\x.dll", PathStyle::Symbolic)
+                .unwrap(),
+            "%SystemRoot%\\<System32>\\x.dll"
+        );
+        assert_eq!(dirids.render("100%", PathStyle::Concrete).unwrap(), "100%");
+        assert_eq!(
+            dirids.render("%99%\\x", PathStyle::Concrete),
+            Err(DiridError::UnknownDirid(99))
+        );
+    }
+
+    #[test]
+    fn test_custom_dirids() {
+        let mut dirids = DiridTable::default();
+        dirids.insert(0x8000, "C:\\Contoso");
+        dirids.insert(
+            13,
+            "C:\\Windows\\System32\\DriverStore\\FileRepository\\audio.inf_amd64",
+        );
+        assert_eq!(
+            dirids
+                .render("%32768%\\x.dll", PathStyle::Symbolic)
+                .unwrap(),
+            "C:\\Contoso\\x.dll"
+        );
+        let dir = DestinationDir {
+            dirid: 13,
+            subdir: Some("bin".to_string()),
+        };
+        assert_eq!(
+            dirids
+                .render_destination(&dir, PathStyle::Concrete)
+                .unwrap(),
+            "C:\\Windows\\System32\\DriverStore\\FileRepository\\audio.inf_amd64\\bin"
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{InfValue, VersionError, WinInfFile};
+
+/// Errors that can occur while parsing a `DriverVer` directive
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DriverVerError {
+    /// The value does not have the `mm/dd/yyyy[,w.x.y.z]` form
+    #[error("Invalid DriverVer format: {0}")]
+    InvalidFormat(String),
+    /// The date part is not a valid `mm/dd/yyyy` date
+    #[error("Invalid DriverVer date: {0}")]
+    InvalidDate(String),
+    /// The version part is not a valid `w.x.y.z` version
+    #[error("Invalid DriverVer version: {0}")]
+    InvalidVersion(String),
+}
+
+/// The date part of a `DriverVer` directive
+///
+/// Dates order chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DriverDate {
+    /// The year, e.g. `2021`
+    pub year: u16,
+    /// The month, `1` to `12`
+    pub month: u8,
+    /// The day of the month, `1` to `31`
+    pub day: u8,
+}
+
+/// The four-part version of a `DriverVer` directive
+///
+/// Each part is a 16-bit number, parts missing from the INF are `0`. Versions order
+/// part by part, most significant part first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DriverVersion {
+    /// The parts of the version, e.g. `[1, 0, 0, 0]` for `1.0.0.0`
+    pub parts: [u16; 4],
+}
+
+/// A parsed `DriverVer=mm/dd/yyyy[,w.x.y.z]` directive
+///
+/// The ordering mirrors how Windows ranks otherwise equal drivers: the newer date
+/// wins, and the higher version only breaks ties between equal dates.
+///
+/// # Examples
+///
+/// ```
+/// use inf_rs::DriverVer;
+///
+/// let older: DriverVer = "07/07/2021,9.0.0.0".parse().unwrap();
+/// let newer: DriverVer = "08/01/2021,1.0.0.0".parse().unwrap();
+/// assert!(newer > older);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DriverVer {
+    /// The driver date
+    pub date: DriverDate,
+    /// The driver version, `0.0.0.0` if the INF does not specify one
+    pub version: DriverVersion,
+}
+
+impl FromStr for DriverDate {
+    type Err = DriverVerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DriverVerError::InvalidDate(s.to_string());
+        let mut parts = s.trim().split('/');
+        let (Some(month), Some(day), Some(year), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let (month, day, year) = (month.trim(), day.trim(), year.trim());
+        if month.len() > 2 || day.len() > 2 || year.len() != 4 {
+            return Err(invalid());
+        }
+        let date = DriverDate {
+            year: year.parse().map_err(|_| invalid())?,
+            month: month.parse().map_err(|_| invalid())?,
+            day: day.parse().map_err(|_| invalid())?,
+        };
+        if !(1..=12).contains(&date.month) || date.day == 0 || date.day > date.days_in_month() {
+            return Err(invalid());
+        }
+        Ok(date)
+    }
+}
+
+impl DriverDate {
+    fn is_leap_year(&self) -> bool {
+        (self.year.is_multiple_of(4) && !self.year.is_multiple_of(100))
+            || self.year.is_multiple_of(400)
+    }
+
+    fn days_in_month(&self) -> u8 {
+        match self.month {
+            2 if self.is_leap_year() => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+}
+
+impl FromStr for DriverVersion {
+    type Err = DriverVerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DriverVerError::InvalidVersion(s.to_string());
+        let mut version = DriverVersion::default();
+        for (idx, part) in s.trim().split('.').enumerate() {
+            // at most four parts, each of them a 16-bit decimal number
+            let slot = version.parts.get_mut(idx).ok_or_else(invalid)?;
+            let part = part.trim();
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            *slot = part.parse().map_err(|_| invalid())?;
+        }
+        Ok(version)
+    }
+}
+
+impl FromStr for DriverVer {
+    type Err = DriverVerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split(',');
+        let date = fields.next().unwrap_or_default();
+        let version = fields.next();
+        if fields.next().is_some() {
+            return Err(DriverVerError::InvalidFormat(s.to_string()));
+        }
+        Ok(DriverVer {
+            date: date.parse()?,
+            version: match version {
+                Some(v) if !v.trim().is_empty() => v.parse()?,
+                _ => DriverVersion::default(),
+            },
+        })
+    }
+}
+
+impl DriverVer {
+    /// Parses the value of a `DriverVer` entry
+    pub fn from_value(value: &InfValue) -> Result<Self, DriverVerError> {
+        match value.fields().as_slice() {
+            [date] => DriverVer::from_str(date),
+            [date, version] => DriverVer::from_str(&format!("{date},{version}")),
+            _ => Err(DriverVerError::InvalidFormat(value.to_string())),
+        }
+    }
+}
+
+impl WinInfFile {
+    /// Returns the parsed `DriverVer` directive of the `[Version]` section
+    ///
+    /// Useful to rank several INF files for the same device, e.g.
+    /// `infs.iter().max_by_key(|inf| inf.driver_ver().ok())` picks the newest one.
+    pub fn driver_ver(&self) -> Result<DriverVer, VersionError> {
+        let value = self
+            .section("Version")
+            .ok_or(VersionError::MissingSection)?
+            .get("DriverVer")
+            .ok_or(VersionError::MissingDirective("DriverVer"))?;
+        Ok(DriverVer::from_value(
+            &self.string_table().expand_value(value)?,
+        )?)
+    }
+}
+
+impl fmt::Display for DriverDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}/{:02}/{:04}", self.month, self.day, self.year)
+    }
+}
+
+impl fmt::Display for DriverVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [w, x, y, z] = self.parts;
+        write!(f, "{w}.{x}.{y}.{z}")
+    }
+}
+
+impl fmt::Display for DriverVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.date, self.version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_driver_ver_parse() {
+        let driver_ver: DriverVer = "03/15/2011, 0.03.15.0011".parse().unwrap();
+        assert_eq!(
+            driver_ver.date,
+            DriverDate {
+                year: 2011,
+                month: 3,
+                day: 15
+            }
+        );
+        assert_eq!(driver_ver.version.parts, [0, 3, 15, 11]);
+
+        let driver_ver: DriverVer = "1/2/2020".parse().unwrap();
+        assert_eq!(driver_ver.version, DriverVersion::default());
+        let driver_ver: DriverVer = "1/2/2020,10.2".parse().unwrap();
+        assert_eq!(driver_ver.version.parts, [10, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_driver_ver_validation() {
+        for invalid_date in [
+            "13/01/2020",
+            "02/30/2021",
+            "00/10/2020",
+            "1/2/20",
+            "1-2-2020",
+        ] {
+            assert!(matches!(
+                invalid_date.parse::<DriverVer>(),
+                Err(DriverVerError::InvalidDate(_))
+            ));
+        }
+        assert!("02/29/2020".parse::<DriverVer>().is_ok());
+        assert!("02/29/2100".parse::<DriverVer>().is_err());
+
+        for invalid_version in ["1.2.3.4.5", "65536.0.0.0", "1..2", "1.-2", "a.b"] {
+            assert!(matches!(
+                format!("01/01/2020,{invalid_version}").parse::<DriverVer>(),
+                Err(DriverVerError::InvalidVersion(_))
+            ));
+        }
+        assert!(matches!(
+            "01/01/2020,1.0,extra".parse::<DriverVer>(),
+            Err(DriverVerError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_driver_ver_ranking() {
+        let parse = |s: &str| s.parse::<DriverVer>().unwrap();
+        assert!(parse("01/01/2022,1.0.0.0") > parse("12/31/2021,9.9.9.9"));
+        assert!(parse("01/01/2022,1.0.0.10") > parse("01/01/2022,1.0.0.9"));
+        assert!(parse("01/01/2022,2.0") > parse("01/01/2022,1.65535.65535.65535"));
+        assert_eq!(parse("01/01/2022,1"), parse("1/1/2022,1.0.0.0"));
+
+        let newest = [
+            "07/07/2021,1.0.0.0",
+            "03/15/2011,0.3.15.11",
+            "07/07/2021,1.0.0.1",
+        ]
+        .into_iter()
+        .map(parse)
+        .max()
+        .unwrap();
+        assert_eq!(newest.to_string(), "07/07/2021,1.0.0.1");
+    }
+}
@@ -0,0 +1,265 @@
+/// Errors that can occur while splitting an INF value into fields
+#[derive(Debug, PartialEq)]
+pub(crate) enum FieldError {
+    /// A double quote was opened but never closed on the same line
+    UnterminatedQuote,
+    /// Text other than a separator, comment or continuation follows a closing quote
+    TextAfterQuote,
+}
+
+/// Finds the `=` separating the key from the value of an entry
+///
+/// An `=` inside a quoted string or a comment does not separate a key.
+pub(crate) fn find_key_separator(line: &str) -> Option<usize> {
+    let mut in_quotes = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => return None,
+            '=' if !in_quotes => return Some(idx),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits (part of) an INF value into comma separated fields
+///
+/// Fields are appended to `fields`, the last element being the field that is still
+/// open. Quoted strings keep their commas, semicolons and whitespace, and `""` inside
+/// a quoted string stands for a single double quote. Unquoted whitespace around a field
+/// is dropped and a `;` outside quotes starts a comment.
+///
+/// Returns `Ok(true)` if the line ends with a continuation backslash, in which case the
+/// next line must be passed in with the same `fields` to continue the open field.
+pub(crate) fn split_fields(text: &str, fields: &mut Vec<String>) -> Result<bool, FieldError> {
+    if fields.is_empty() {
+        fields.push(String::new());
+    }
+    let mut has_content = fields.last().is_some_and(|f| !f.is_empty());
+    // whitespace is only kept when more text of the same field follows
+    let mut pending_ws = String::new();
+
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            ';' => break,
+            ',' => {
+                fields.push(String::new());
+                has_content = false;
+                pending_ws.clear();
+            }
+            '"' => {
+                let field = fields.last_mut().unwrap();
+                if has_content {
+                    field.push_str(&pending_ws);
+                }
+                pending_ws.clear();
+                has_content = true;
+                loop {
+                    match chars.next() {
+                        None => return Err(FieldError::UnterminatedQuote),
+                        Some((_, '"')) => {
+                            // two double quotes inside a quoted string is an escaped quote
+                            if chars.peek().is_some_and(|(_, c)| *c == '"') {
+                                chars.next();
+                                field.push('"');
+                            } else {
+                                break;
+                            }
+                        }
+                        Some((_, c)) => field.push(c),
+                    }
+                }
+                let rest = match chars.peek() {
+                    Some((next_idx, _)) => text[*next_idx..].trim_start(),
+                    None => "",
+                };
+                if !(rest.is_empty() || rest.starts_with([',', ';', '\\'])) {
+                    return Err(FieldError::TextAfterQuote);
+                }
+            }
+            '\\' => {
+                // multiple backslashes at the end, windows treat only the last one as
+                // line continuator and ignores rest
+                let rest = text[idx..].trim_start_matches('\\').trim_start();
+                if rest.is_empty() || rest.starts_with(';') {
+                    if has_content {
+                        fields.last_mut().unwrap().push_str(&pending_ws);
+                    }
+                    return Ok(true);
+                }
+                let field = fields.last_mut().unwrap();
+                field.push_str(&pending_ws);
+                pending_ws.clear();
+                field.push(c);
+                has_content = true;
+            }
+            c if c.is_whitespace() => {
+                if has_content {
+                    pending_ws.push(c);
+                }
+            }
+            c => {
+                let field = fields.last_mut().unwrap();
+                field.push_str(&pending_ws);
+                pending_ws.clear();
+                field.push(c);
+                has_content = true;
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Returns (part of) an INF value as it is written, without a comment and, if the line
+/// is `continued`, without the continuation backslashes at the end
+pub(crate) fn value_text(text: &str, continued: bool) -> &str {
+    let mut in_quotes = false;
+    let end = text
+        .char_indices()
+        .find(|(_, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                false
+            }
+            ';' => !in_quotes,
+            _ => false,
+        })
+        .map_or(text.len(), |(idx, _)| idx);
+    let text = text[..end].trim_end();
+    if continued {
+        text.trim_end_matches('\\')
+    } else {
+        text
+    }
+}
+
+/// Returns, for each field of a value as it is written, whether the field is quoted
+///
+/// `text` is a value without its comment and continuation backslashes, as returned by
+/// [`value_text`].
+pub(crate) fn quoted_fields(text: &str) -> Vec<bool> {
+    let mut quoted = vec![false];
+    let mut in_quotes = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                *quoted.last_mut().unwrap() = true;
+            }
+            ',' if !in_quotes => quoted.push(false),
+            _ => {}
+        }
+    }
+    quoted
+}
+
+/// Parses a numeric INF field, either decimal or hexadecimal with a `0x` prefix
+pub(crate) fn parse_number(field: &str) -> Option<u32> {
+    let field = field.trim();
+    match field
+        .strip_prefix("0x")
+        .or_else(|| field.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => field.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(text: &str) -> Vec<String> {
+        let mut fields = vec![];
+        assert_eq!(split_fields(text, &mut fields), Ok(false));
+        fields
+    }
+
+    #[test]
+    fn test_split_fields_basic() {
+        assert_eq!(
+            split("AudioCodec, %SPSVCINST_ASSOCSERVICE%, Audio_Service_Inst"),
+            vec![
+                "AudioCodec",
+                "%SPSVCINST_ASSOCSERVICE%",
+                "Audio_Service_Inst"
+            ]
+        );
+        assert_eq!(split("1,,"), vec!["1", "", ""]);
+        assert_eq!(split("$WINDOWS NT$"), vec!["$WINDOWS NT$"]);
+        assert_eq!(split(""), vec![""]);
+    }
+
+    #[test]
+    fn test_split_fields_quoted() {
+        assert_eq!(split("%DiskId1%,,,\"\""), vec!["%DiskId1%", "", "", ""]);
+        assert_eq!(
+            split("\"{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},1\""),
+            vec!["{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},1"]
+        );
+        assert_eq!(split("\" a;b \" , c"), vec![" a;b ", "c"]);
+        assert_eq!(split("\"say \"\"hi\"\"\""), vec!["say \"hi\""]);
+    }
+
+    #[test]
+    fn test_split_fields_comments_and_errors() {
+        assert_eq!(split("1   ; SERVICE_KERNEL_DRIVER"), vec!["1"]);
+        let mut fields = vec![];
+        assert_eq!(
+            split_fields("\"unterminated", &mut fields),
+            Err(FieldError::UnterminatedQuote)
+        );
+        let mut fields = vec![];
+        assert_eq!(
+            split_fields("\"quoted\" trailing", &mut fields),
+            Err(FieldError::TextAfterQuote)
+        );
+    }
+
+    #[test]
+    fn test_split_fields_continuation() {
+        let mut fields = vec![];
+        assert_eq!(split_fields("01,02,\\", &mut fields), Ok(true));
+        assert_eq!(split_fields("03,04", &mut fields), Ok(false));
+        assert_eq!(fields, vec!["01", "02", "03", "04"]);
+    }
+
+    #[test]
+    fn test_value_text() {
+        assert_eq!(
+            value_text("1, \"a;b\" ,, ; comment", false),
+            "1, \"a;b\" ,,"
+        );
+        assert_eq!(value_text("01,02,\\", true), "01,02,");
+        assert_eq!(value_text("a\\", false), "a\\");
+        assert_eq!(value_text("\"open", false), "\"open");
+    }
+
+    #[test]
+    fn test_quoted_fields() {
+        assert_eq!(quoted_fields("\"$Windows NT$\""), [true]);
+        assert_eq!(
+            quoted_fields("HKR,,\"a, \"\"b\"\"\",,1"),
+            [false, false, true, false, false]
+        );
+        assert_eq!(quoted_fields(""), [false]);
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("13"), Some(13));
+        assert_eq!(parse_number(" 0x00010001 "), Some(0x10001));
+        assert_eq!(parse_number("0XfF"), Some(255));
+        assert_eq!(parse_number("0x"), None);
+        assert_eq!(parse_number("%REG_DWORD%"), None);
+    }
+
+    #[test]
+    fn test_find_key_separator() {
+        assert_eq!(find_key_separator("key = value"), Some(4));
+        assert_eq!(find_key_separator("HKR,,\"a=b\",,1"), None);
+        assert_eq!(find_key_separator("value ; a=b"), None);
+    }
+}
@@ -0,0 +1,344 @@
+use std::fmt;
+
+use crate::fields::parse_number;
+use crate::{InfEntry, InfSection, StringTable, StringsError, WinInfFile};
+
+/// The DIRID Setup uses when an INF file has no `DefaultDestDir` entry
+const DEFAULT_DIRID: u32 = 11;
+
+/// Errors that can occur while resolving file operations
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum FilesError {
+    /// A section containing or referenced by a file directive does not exist
+    #[error("Missing file list section: {0}")]
+    MissingSection(String),
+    /// A file list entry does not name a file or has invalid flags
+    #[error("Invalid file list entry: {0}")]
+    InvalidEntry(String),
+    /// A `[DestinationDirs]` entry does not start with a DIRID
+    #[error("Invalid DestinationDirs entry: {0}")]
+    InvalidDestinationDir(String),
+    /// A `%strkey%` token could not be expanded
+    #[error("Failed to expand string token: {0}")]
+    Strings(#[from] StringsError),
+}
+
+/// The directive a file operation comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileOperationKind {
+    /// `CopyFiles`
+    Copy,
+    /// `DelFiles`
+    Delete,
+    /// `RenFiles`
+    Rename,
+}
+
+impl FileOperationKind {
+    /// Returns the name of the directive, e.g. `CopyFiles`
+    pub fn directive(&self) -> &'static str {
+        match self {
+            FileOperationKind::Copy => "CopyFiles",
+            FileOperationKind::Delete => "DelFiles",
+            FileOperationKind::Rename => "RenFiles",
+        }
+    }
+}
+
+/// A destination directory from `[DestinationDirs]`
+///
+/// `file-list-section = dirid[,subdir]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DestinationDir {
+    /// The DIRID of the directory, e.g. `13` for the driver store
+    pub dirid: u32,
+    /// The subdirectory below the DIRID
+    pub subdir: Option<String>,
+}
+
+impl fmt::Display for DestinationDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}%", self.dirid)?;
+        if let Some(subdir) = &self.subdir {
+            write!(f, "\\{subdir}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A file copied, deleted or renamed by a `CopyFiles`, `DelFiles` or `RenFiles` directive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOperation {
+    /// The directive the operation comes from
+    pub kind: FileOperationKind,
+    /// The file list section, `None` for the `CopyFiles = @file` form
+    pub section: Option<String>,
+    /// The name of the file in the destination directory
+    ///
+    /// For `RenFiles` this is the new name of the file.
+    pub destination_file: String,
+    /// The name of the source file
+    ///
+    /// For `CopyFiles` this is the file in the package, which defaults to the destination
+    /// name. For `RenFiles` it is the old name of the file, and for `DelFiles` the file itself.
+    pub source_file: String,
+    /// The directory the operation applies to
+    pub destination: DestinationDir,
+    /// The `COPYFLG_*` or `DELFLG_*` flags, `0` when absent
+    pub flags: u32,
+}
+
+impl WinInfFile {
+    /// Returns the destination directory of a file list section
+    ///
+    /// The section's own `[DestinationDirs]` entry is used if there is one, then
+    /// `DefaultDestDir`. Without either, Setup copies to DIRID 11.
+    pub fn destination_dir(
+        &self,
+        file_list_section: Option<&str>,
+    ) -> Result<DestinationDir, FilesError> {
+        self.destination_dir_with(file_list_section, &self.string_table())
+    }
+
+    /// Returns the destination directory of a file list section, expanding tokens with
+    /// `strings`
+    pub(crate) fn destination_dir_with(
+        &self,
+        file_list_section: Option<&str>,
+        strings: &StringTable,
+    ) -> Result<DestinationDir, FilesError> {
+        let Some(dirs) = self.section("DestinationDirs") else {
+            return Ok(DestinationDir {
+                dirid: DEFAULT_DIRID,
+                subdir: None,
+            });
+        };
+        let value = file_list_section
+            .and_then(|name| dirs.get(name))
+            .or_else(|| dirs.get("DefaultDestDir"));
+        let Some(value) = value else {
+            return Ok(DestinationDir {
+                dirid: DEFAULT_DIRID,
+                subdir: None,
+            });
+        };
+        let invalid = || FilesError::InvalidDestinationDir(value.to_string());
+        let dirid = strings.expand(value.field(0).ok_or_else(invalid)?)?;
+        let subdir = value
+            .field(1)
+            .filter(|subdir| !subdir.is_empty())
+            .map(|subdir| strings.expand(subdir))
+            .transpose()?;
+        Ok(DestinationDir {
+            dirid: parse_number(&dirid).ok_or_else(invalid)?,
+            subdir,
+        })
+    }
+
+    /// Parses one line of a file list section
+    fn file_operation(
+        &self,
+        kind: FileOperationKind,
+        section: &InfSection,
+        entry: &InfEntry,
+        destination: &DestinationDir,
+        strings: &StringTable,
+    ) -> Result<FileOperation, FilesError> {
+        let invalid = || FilesError::InvalidEntry(format!("{entry:?}"));
+        let InfEntry::OnlyValue(value) = entry else {
+            return Err(invalid());
+        };
+        let fields = value
+            .fields()
+            .into_iter()
+            .map(|field| strings.expand(field))
+            .collect::<Result<Vec<_>, _>>()?;
+        let field = |idx: usize| fields.get(idx).filter(|f| !f.is_empty());
+
+        let destination_file = field(0).cloned().ok_or_else(invalid)?;
+        let (source_file, flags) = match kind {
+            // destination-file-name[,source-file-name][,unused][,flag]
+            FileOperationKind::Copy => (field(1).cloned(), field(3)),
+            // file-name[,unused][,unused][,flag]
+            FileOperationKind::Delete => (None, field(3)),
+            // new-dest-file-name,old-source-file-name
+            FileOperationKind::Rename => (Some(field(1).cloned().ok_or_else(invalid)?), None),
+        };
+        let flags = match flags {
+            Some(flags) => parse_number(flags).ok_or_else(invalid)?,
+            None => 0,
+        };
+        Ok(FileOperation {
+            kind,
+            section: Some(section.name().to_string()),
+            source_file: source_file.unwrap_or_else(|| destination_file.clone()),
+            destination_file,
+            destination: destination.clone(),
+            flags,
+        })
+    }
+
+    fn file_operations(
+        &self,
+        kind: FileOperationKind,
+        section_name: &str,
+    ) -> Result<Vec<FileOperation>, FilesError> {
+        let section = self
+            .section(section_name)
+            .ok_or_else(|| FilesError::MissingSection(section_name.to_string()))?;
+        let strings = self.string_table();
+        let mut operations = Vec::new();
+        for value in section.get_all(kind.directive()) {
+            for name in value.fields().into_iter().filter(|name| !name.is_empty()) {
+                if let Some(file) = name.strip_prefix('@') {
+                    // only CopyFiles has an inline form, copying a single file to DefaultDestDir
+                    if kind != FileOperationKind::Copy {
+                        return Err(FilesError::InvalidEntry(name.to_string()));
+                    }
+                    let file = strings.expand(file)?;
+                    operations.push(FileOperation {
+                        kind,
+                        section: None,
+                        destination_file: file.clone(),
+                        source_file: file,
+                        destination: self.destination_dir_with(None, &strings)?,
+                        flags: 0,
+                    });
+                    continue;
+                }
+                let list = self
+                    .section(name)
+                    .ok_or_else(|| FilesError::MissingSection(name.to_string()))?;
+                let destination = self.destination_dir_with(Some(list.name()), &strings)?;
+                for entry in list.entries() {
+                    operations.push(self.file_operation(
+                        kind,
+                        list,
+                        entry,
+                        &destination,
+                        &strings,
+                    )?);
+                }
+            }
+        }
+        Ok(operations)
+    }
+
+    /// Expands the `CopyFiles` directives of a section, usually a DDInstall section
+    ///
+    /// Both file list section references and the inline `@file` form are expanded, in the
+    /// order they appear.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/sampledisplay.inf")).unwrap();
+    /// let files = inf_file.copy_files("KDODSamp_Inst").unwrap();
+    /// assert_eq!(files[0].source_file, "SampleDisplay.sys");
+    /// assert_eq!(files[0].destination.dirid, 12);
+    /// ```
+    pub fn copy_files(&self, section_name: &str) -> Result<Vec<FileOperation>, FilesError> {
+        self.file_operations(FileOperationKind::Copy, section_name)
+    }
+
+    /// Expands the `DelFiles` directives of a section
+    pub fn del_files(&self, section_name: &str) -> Result<Vec<FileOperation>, FilesError> {
+        self.file_operations(FileOperationKind::Delete, section_name)
+    }
+
+    /// Expands the `RenFiles` directives of a section
+    pub fn ren_files(&self, section_name: &str) -> Result<Vec<FileOperation>, FilesError> {
+        self.file_operations(FileOperationKind::Rename, section_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> WinInfFile {
+        let mut inf_file = WinInfFile::default();
+        inf_file.parse_str(content).unwrap();
+        inf_file
+    }
+
+    #[test]
+    fn test_copy_files_destination_dirs() {
+        let inf_file = parse(concat!(
+            "[DestinationDirs]\n",
+            "DefaultDestDir = 13\n",
+            "Drivers = 12, %SubDir%\n",
+            "[Inst]\n",
+            "CopyFiles = Drivers, @inline.dll\n",
+            "CopyFiles = Other\n",
+            "DelFiles = Drivers\n",
+            "RenFiles = Renames\n",
+            "[Drivers]\n",
+            "new.sys, old.sys,, 0x4\n",
+            "plain.sys\n",
+            "[Other]\n",
+            "other.dll\n",
+            "[Renames]\n",
+            "new.dll, old.dll\n",
+            "[Strings]\n",
+            "SubDir = Contoso\n",
+        ));
+        let files = inf_file.copy_files("Inst").unwrap();
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0].destination_file, "new.sys");
+        assert_eq!(files[0].source_file, "old.sys");
+        assert_eq!(files[0].flags, 4);
+        assert_eq!(files[0].destination.to_string(), "%12%\\Contoso");
+        assert_eq!(files[1].source_file, "plain.sys");
+        assert_eq!(files[2].section, None);
+        assert_eq!(files[2].source_file, "inline.dll");
+        assert_eq!(files[2].destination.to_string(), "%13%");
+        assert_eq!(files[3].section.as_deref(), Some("Other"));
+        assert_eq!(files[3].destination.dirid, 13);
+
+        let files = inf_file.del_files("Inst").unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].kind, FileOperationKind::Delete);
+        assert_eq!(files[0].source_file, "new.sys");
+
+        let files = inf_file.ren_files("Inst").unwrap();
+        assert_eq!(files[0].destination_file, "new.dll");
+        assert_eq!(files[0].source_file, "old.dll");
+        assert_eq!(files[0].destination.dirid, 13);
+    }
+
+    #[test]
+    fn test_copy_files_defaults_and_errors() {
+        let inf_file = parse("[Inst]\nCopyFiles = @a.sys\n");
+        assert_eq!(
+            inf_file.copy_files("Inst").unwrap()[0].destination.dirid,
+            11
+        );
+
+        let copy_files = |content: &str| parse(content).copy_files("Inst");
+        assert_eq!(
+            copy_files("[Inst]\nCopyFiles = Missing\n"),
+            Err(FilesError::MissingSection("Missing".to_string()))
+        );
+        assert!(matches!(
+            copy_files("[Inst]\nCopyFiles = Files\n[Files]\na.sys,,,flags\n"),
+            Err(FilesError::InvalidEntry(_))
+        ));
+        assert!(matches!(
+            copy_files("[DestinationDirs]\nDefaultDestDir = x\n[Inst]\nCopyFiles = @a.sys\n"),
+            Err(FilesError::InvalidDestinationDir(_))
+        ));
+        assert!(matches!(
+            parse("[Inst]\nDelFiles = @a.sys\n").del_files("Inst"),
+            Err(FilesError::InvalidEntry(_))
+        ));
+        assert!(matches!(
+            parse("[Inst]\nRenFiles = Files\n[Files]\na.sys\n").ren_files("Inst"),
+            Err(FilesError::InvalidEntry(_))
+        ));
+    }
+}
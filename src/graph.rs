@@ -0,0 +1,567 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::{InfEntry, InfSection, Span, WinInfFile};
+
+/// Sections Windows reads by name, with any decoration
+const ROOT_SECTIONS: &[&str] = &[
+    "Version",
+    "Manufacturer",
+    "DestinationDirs",
+    "SourceDisksNames",
+    "SourceDisksFiles",
+    "Strings",
+    "DefaultInstall",
+    "DefaultUninstall",
+    "ClassInstall32",
+    "InterfaceInstall32",
+    "ControlFlags",
+    "SignatureAttributes",
+];
+
+/// Directives whose fields are all section names
+const SECTION_LIST_DIRECTIVES: &[&str] = &[
+    "CopyFiles",
+    "DelFiles",
+    "RenFiles",
+    "AddReg",
+    "DelReg",
+    "BitReg",
+    "Ini2Reg",
+    "UpdateInis",
+    "UpdateIniFields",
+    "RegisterDlls",
+    "UnregisterDlls",
+    "ProfileItems",
+    "LogConfig",
+    "AddProperty",
+    "DelProperty",
+    "AddPowerSetting",
+    "Needs",
+];
+
+/// Directives with a section name in some of their fields, by field index
+const SECTION_FIELD_DIRECTIVES: &[(&str, &[usize])] = &[
+    ("AddService", &[2, 3]),
+    ("AddInterface", &[2]),
+    ("AddComponent", &[2]),
+    ("AddSoftware", &[2]),
+    ("AddEventProvider", &[1]),
+    ("KmdfService", &[1]),
+    ("UmdfService", &[1]),
+];
+
+/// Sections Windows reads along with an install section, e.g. `[Inst.NT.Services]`
+const COMPANION_SECTIONS: &[&str] = &[
+    "Services",
+    "HW",
+    "CoInstallers",
+    "Interfaces",
+    "Wdf",
+    "Events",
+    "Components",
+    "Software",
+    "FactDef",
+    "LogConfigOverride",
+];
+
+/// A reference from an entry of one section to another section by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionReference {
+    /// The section the entry is in
+    pub from: String,
+    /// The directive that makes the reference, e.g. `CopyFiles`
+    ///
+    /// Entries of the `[Manufacturer]`, models and `[InterfaceInstall32]` sections are
+    /// not directives; their references are labelled `Manufacturer`, `Model` and
+    /// `InterfaceInstall32`.
+    pub directive: String,
+    /// The referenced section name, with `%strkey%` tokens expanded
+    pub name: String,
+    /// `true` if the name is an install section, so that sections with a platform
+    /// decoration or a companion suffix, such as `name.NTamd64` or `name.NT.Services`,
+    /// are referenced as well
+    pub decorated: bool,
+    /// `true` if the section does not need to be in this file
+    ///
+    /// `Needs` sections can come from an INF file pulled in with `Include`, and the
+    /// undecorated models section is only a fallback when decorated ones are listed.
+    pub optional: bool,
+    /// Where the entry is in the parsed content
+    pub span: Option<Span>,
+}
+
+impl SectionReference {
+    /// Returns `true` if the reference points at the section
+    pub fn matches(&self, section: &str) -> bool {
+        if self.decorated {
+            is_install_section(section, &self.name)
+        } else {
+            section.eq_ignore_ascii_case(&self.name)
+        }
+    }
+}
+
+/// A link between two sections of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionEdge {
+    /// The referencing section
+    pub from: String,
+    /// The referenced section
+    pub to: String,
+    /// The directive that makes the reference
+    pub directive: String,
+}
+
+/// The references between the sections of an INF file
+///
+/// Built by [`WinInfFile::section_graph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionGraph {
+    sections: Vec<String>,
+    roots: Vec<String>,
+    references: Vec<SectionReference>,
+    edges: Vec<SectionEdge>,
+}
+
+impl SectionGraph {
+    /// Returns the sections Windows reads by name, such as `[Version]` or `[DefaultInstall]`
+    pub fn roots(&self) -> &[String] {
+        &self.roots
+    }
+
+    /// Returns every section reference, in file order
+    pub fn references(&self) -> &[SectionReference] {
+        &self.references
+    }
+
+    /// Returns the links to the sections that exist, once each, in file order
+    pub fn edges(&self) -> &[SectionEdge] {
+        &self.edges
+    }
+
+    /// Returns the references to sections that are not in the file
+    pub fn dangling_references(&self) -> impl Iterator<Item = &SectionReference> {
+        self.references.iter().filter(|reference| {
+            !reference.optional
+                && !self
+                    .sections
+                    .iter()
+                    .any(|section| reference.matches(section))
+        })
+    }
+
+    /// Returns the sections that cannot be reached from a root, in file order
+    ///
+    /// `[DestinationDirs]` entries are references too, but they only say where files go,
+    /// so they do not make a file list section reachable.
+    pub fn unreachable_sections(&self) -> Vec<&str> {
+        let mut reachable: HashSet<String> = self
+            .roots
+            .iter()
+            .map(|root| root.to_ascii_lowercase())
+            .collect();
+        let mut queue: Vec<&str> = self.roots.iter().map(String::as_str).collect();
+        while let Some(section) = queue.pop() {
+            for edge in &self.edges {
+                if edge.from.eq_ignore_ascii_case(section)
+                    && !edge.directive.eq_ignore_ascii_case("DestinationDirs")
+                    && reachable.insert(edge.to.to_ascii_lowercase())
+                {
+                    queue.push(&edge.to);
+                }
+            }
+        }
+        self.sections
+            .iter()
+            .filter(|section| !reachable.contains(&section.to_ascii_lowercase()))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Returns the graph in the Graphviz DOT language
+    ///
+    /// Roots are drawn as boxes, unreachable sections with a dashed outline and dangling
+    /// references as red edges to a node that is not a section.
+    pub fn to_dot(&self) -> String {
+        let quote = |name: &str| format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""));
+        let unreachable = self.unreachable_sections();
+
+        let mut dot = String::from("digraph inf {\n");
+        for section in &self.sections {
+            let mut attributes = vec![];
+            if self.roots.contains(section) {
+                attributes.push("shape=box");
+            }
+            if unreachable.contains(&section.as_str()) {
+                attributes.push("style=dashed");
+            }
+            if attributes.is_empty() {
+                writeln!(dot, "    {};", quote(section)).unwrap();
+            } else {
+                writeln!(dot, "    {} [{}];", quote(section), attributes.join(", ")).unwrap();
+            }
+        }
+        for edge in &self.edges {
+            writeln!(
+                dot,
+                "    {} -> {} [label={}];",
+                quote(&edge.from),
+                quote(&edge.to),
+                quote(&edge.directive)
+            )
+            .unwrap();
+        }
+        for reference in self.dangling_references() {
+            writeln!(
+                dot,
+                "    {} -> {} [label={}, color=red];",
+                quote(&reference.from),
+                quote(&reference.name),
+                quote(&reference.directive)
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+pub(crate) fn base_name(name: &str) -> &str {
+    name.split('.').next().unwrap_or(name)
+}
+
+/// Returns `true` if `name` is the install section `base`, optionally with a platform
+/// decoration and a companion suffix, e.g. `base.NTamd64` or `base.NT.Services`
+fn is_install_section(name: &str, base: &str) -> bool {
+    let rest = match name.get(..base.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(base) => &name[base.len()..],
+        _ => return false,
+    };
+    let Some(rest) = rest.strip_prefix('.') else {
+        return rest.is_empty();
+    };
+    let mut parts: Vec<&str> = rest.split('.').collect();
+    if parts[0]
+        .get(..2)
+        .is_some_and(|nt| nt.eq_ignore_ascii_case("NT"))
+    {
+        parts.remove(0);
+    }
+    match parts[..] {
+        [] => true,
+        [companion] => COMPANION_SECTIONS
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(companion)),
+        _ => false,
+    }
+}
+
+/// Returns the models sections a `[Manufacturer]` entry refers to, and whether each is
+/// optional
+fn models_sections(entry: &InfEntry) -> Vec<(String, bool)> {
+    let (models, decorations) = match entry {
+        InfEntry::KeyValue(_, Some(value)) => {
+            let fields = value.fields();
+            match fields.split_first() {
+                Some((models, decorations)) => (models.to_string(), decorations.to_vec()),
+                None => return vec![],
+            }
+        }
+        // `%strkey%` alone names a models section after the token
+        InfEntry::OnlyValue(value) => {
+            let name = value.field(0).unwrap_or_default();
+            (name.trim_matches('%').to_string(), vec![])
+        }
+        InfEntry::KeyValue(_, None) => return vec![],
+    };
+    if models.is_empty() {
+        return vec![];
+    }
+    let decorations: Vec<&str> = decorations.into_iter().filter(|d| !d.is_empty()).collect();
+    let mut sections: Vec<(String, bool)> = decorations
+        .iter()
+        .map(|decoration| (format!("{models}.{decoration}"), false))
+        .collect();
+    sections.push((models, !decorations.is_empty()));
+    sections
+}
+
+/// Returns the directive label, section names and whether decorated sections count for
+/// each reference of an entry
+fn entry_references<'a>(
+    section: &InfSection,
+    entry: &'a InfEntry,
+    is_models: bool,
+) -> Vec<(&'static str, &'a str, bool)> {
+    let fields = entry
+        .value()
+        .map(|value| value.fields())
+        .unwrap_or_default();
+    // install sections are looked up with their decorations and companions
+    if is_models {
+        return fields
+            .first()
+            .map(|name| vec![("Model", *name, true)])
+            .unwrap_or_default();
+    }
+    if base_name(section.name()).eq_ignore_ascii_case("InterfaceInstall32") {
+        return fields
+            .first()
+            .map(|name| vec![("InterfaceInstall32", *name, true)])
+            .unwrap_or_default();
+    }
+
+    let Some(key) = entry.key() else {
+        return vec![];
+    };
+    if section.name().eq_ignore_ascii_case("DestinationDirs") {
+        if key.eq_ignore_ascii_case("DefaultDestDir") {
+            return vec![];
+        }
+        return vec![("DestinationDirs", key, false)];
+    }
+    if let Some(directive) = SECTION_LIST_DIRECTIVES
+        .iter()
+        .find(|directive| directive.eq_ignore_ascii_case(key))
+    {
+        return fields
+            .into_iter()
+            .filter(|name| !name.is_empty() && !name.starts_with('@'))
+            .map(|name| (*directive, name, false))
+            .collect();
+    }
+    SECTION_FIELD_DIRECTIVES
+        .iter()
+        .find(|(directive, _)| directive.eq_ignore_ascii_case(key))
+        .map(|(directive, indexes)| {
+            indexes
+                .iter()
+                .filter_map(|idx| fields.get(*idx))
+                .filter(|name| !name.is_empty())
+                .map(|name| (*directive, *name, false))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl WinInfFile {
+    /// Returns the references between the sections of the file
+    ///
+    /// References come from the `[Manufacturer]` entries to the models sections, from the
+    /// models entries and `[InterfaceInstall32]` entries to install sections, from
+    /// `[DestinationDirs]` to file list sections and from directives such as `CopyFiles`,
+    /// `AddReg`, `Needs` or `AddService` to the sections they name. `Include` names INF
+    /// files rather than sections and makes no reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file
+    ///     .parse_str(concat!(
+    ///         "[DefaultInstall]\n",
+    ///         "CopyFiles = Files\n",
+    ///         "AddReg = Reg\n",
+    ///         "[Files]\n",
+    ///         "a.sys\n",
+    ///         "[Old]\n",
+    ///     ))
+    ///     .unwrap();
+    /// let graph = inf_file.section_graph();
+    /// assert_eq!(graph.edges()[0].to, "Files");
+    /// let dangling: Vec<_> = graph.dangling_references().map(|r| r.name.as_str()).collect();
+    /// assert_eq!(dangling, ["Reg"]);
+    /// assert_eq!(graph.unreachable_sections(), ["Old"]);
+    /// assert!(graph.to_dot().contains("\"DefaultInstall\" -> \"Files\" [label=\"CopyFiles\"];"));
+    /// ```
+    pub fn section_graph(&self) -> SectionGraph {
+        let strings = self.string_table();
+        let expand = |name: &str| strings.expand(name).unwrap_or_else(|_| name.to_string());
+
+        let mut references = vec![];
+        if let Some(manufacturer) = self.section("Manufacturer") {
+            for (entry, span) in manufacturer.entries_with_spans() {
+                for (name, optional) in models_sections(entry) {
+                    references.push(SectionReference {
+                        from: manufacturer.name().to_string(),
+                        directive: "Manufacturer".to_string(),
+                        name: expand(&name),
+                        decorated: false,
+                        optional,
+                        span,
+                    });
+                }
+            }
+        }
+        let models: Vec<String> = references.iter().map(|r| r.name.clone()).collect();
+
+        for section in &self.sections {
+            if section.name().eq_ignore_ascii_case("Manufacturer") {
+                continue;
+            }
+            let is_models = models
+                .iter()
+                .any(|m| m.eq_ignore_ascii_case(section.name()));
+            for (entry, span) in section.entries_with_spans() {
+                for (directive, name, decorated) in entry_references(section, entry, is_models) {
+                    references.push(SectionReference {
+                        from: section.name().to_string(),
+                        directive: directive.to_string(),
+                        name: expand(name),
+                        decorated,
+                        optional: directive == "Needs",
+                        span,
+                    });
+                }
+            }
+        }
+
+        let mut edges = vec![];
+        for reference in &references {
+            for section in self
+                .sections
+                .values()
+                .filter(|s| reference.matches(s.name()))
+            {
+                let edge = SectionEdge {
+                    from: reference.from.clone(),
+                    to: section.name().to_string(),
+                    directive: reference.directive.clone(),
+                };
+                if !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+            }
+        }
+
+        SectionGraph {
+            sections: self
+                .sections
+                .values()
+                .map(|s| s.name().to_string())
+                .collect(),
+            roots: self
+                .sections
+                .values()
+                .filter(|s| {
+                    ROOT_SECTIONS
+                        .iter()
+                        .any(|root| base_name(s.name()).eq_ignore_ascii_case(root))
+                })
+                .map(|s| s.name().to_string())
+                .collect(),
+            references,
+            edges,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_section_graph() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\n",
+                "[Manufacturer]\n",
+                "%Mfg% = Models, NTamd64, NTarm64\n",
+                "[Models.NTamd64]\n",
+                "%Desc% = Inst, PCI\\VEN_1234\n",
+                "[Inst.NT]\n",
+                "CopyFiles = Files, @a.sys\n",
+                "Include = machine.inf\n",
+                "Needs = Machine.Install\n",
+                "[Inst.NT.Services]\n",
+                "AddService = svc, 2, Svc_Inst, Svc_Log\n",
+                "[Svc_Inst]\n",
+                "AddReg = %RegSection%\n",
+                "[Svc_Reg]\n",
+                "[Files]\n",
+                "[Listed]\n",
+                "[DestinationDirs]\n",
+                "Listed = 13\n",
+                "DefaultDestDir = 13\n",
+                "[Strings]\n",
+                "RegSection = Svc_Reg\n",
+            ))
+            .unwrap();
+        let graph = inf_file.section_graph();
+
+        let edges: Vec<_> = graph
+            .edges()
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                ("Manufacturer", "Models.NTamd64"),
+                ("Models.NTamd64", "Inst.NT"),
+                ("Models.NTamd64", "Inst.NT.Services"),
+                ("Inst.NT", "Files"),
+                ("Inst.NT.Services", "Svc_Inst"),
+                ("Svc_Inst", "Svc_Reg"),
+                ("DestinationDirs", "Listed"),
+            ]
+        );
+        let dangling: Vec<_> = graph
+            .dangling_references()
+            .map(|r| {
+                (
+                    r.directive.as_str(),
+                    r.name.as_str(),
+                    r.span.unwrap().start.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            dangling,
+            [
+                ("Manufacturer", "Models.NTarm64", 3),
+                ("AddService", "Svc_Log", 11)
+            ]
+        );
+        assert_eq!(graph.unreachable_sections(), ["Listed"]);
+        assert_eq!(
+            graph.roots(),
+            ["Version", "Manufacturer", "DestinationDirs", "Strings"]
+        );
+    }
+
+    #[test]
+    fn test_install_section_names() {
+        assert!(is_install_section("Inst", "Inst"));
+        assert!(is_install_section("inst.ntAMD64", "Inst"));
+        assert!(is_install_section("Inst.NT.Services", "Inst"));
+        assert!(is_install_section("Inst.HW", "Inst"));
+        assert!(!is_install_section("Inst.Copy", "Inst"));
+        assert!(!is_install_section("Inst.NT.Copy", "Inst"));
+        assert!(!is_install_section("Install", "Inst"));
+    }
+
+    #[test]
+    fn test_section_graph_dot() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str("[DefaultInstall]\nAddReg = Reg, Missing\n[Reg]\n[Loose]\n")
+            .unwrap();
+        assert_eq!(
+            inf_file.section_graph().to_dot(),
+            concat!(
+                "digraph inf {\n",
+                "    \"DefaultInstall\" [shape=box];\n",
+                "    \"Reg\";\n",
+                "    \"Loose\" [style=dashed];\n",
+                "    \"DefaultInstall\" -> \"Reg\" [label=\"AddReg\"];\n",
+                "    \"DefaultInstall\" -> \"Missing\" [label=\"AddReg\", color=red];\n",
+                "}\n",
+            )
+        );
+    }
+}
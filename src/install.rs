@@ -0,0 +1,155 @@
+use crate::{InfSection, Platform, WinInfFile};
+
+/// A DDInstall section selected for a platform, with its companion sections
+///
+/// Companion sections share the decoration of the selected DDInstall section, so
+/// for `[Audio_Device.NT]` the services are in `[Audio_Device.NT.Services]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstallSection<'a> {
+    /// The name of the install section, without decoration
+    pub name: String,
+    /// The platform decoration of the selected section, e.g. `NTamd64` or `NT`
+    pub decoration: Option<String>,
+    /// The selected DDInstall section
+    pub section: &'a InfSection,
+    /// The `.Services` section
+    pub services: Option<&'a InfSection>,
+    /// The `.HW` section
+    pub hw: Option<&'a InfSection>,
+    /// The `.CoInstallers` section
+    pub coinstallers: Option<&'a InfSection>,
+    /// The `.Interfaces` section
+    pub interfaces: Option<&'a InfSection>,
+    /// The `.Wdf` section
+    pub wdf: Option<&'a InfSection>,
+    /// The `.Components` section
+    pub components: Option<&'a InfSection>,
+}
+
+impl<'a> InstallSection<'a> {
+    /// Iterates over the companion sections that exist, with their suffix
+    pub fn companions(&self) -> impl Iterator<Item = (&'static str, &'a InfSection)> + '_ {
+        [
+            ("Services", self.services),
+            ("HW", self.hw),
+            ("CoInstallers", self.coinstallers),
+            ("Interfaces", self.interfaces),
+            ("Wdf", self.wdf),
+            ("Components", self.components),
+        ]
+        .into_iter()
+        .filter_map(|(suffix, section)| section.map(|s| (suffix, s)))
+    }
+}
+
+impl WinInfFile {
+    /// Returns the DDInstall section Setup would use on the platform
+    ///
+    /// Setup looks for the install section decorated with the platform architecture
+    /// (e.g. `[KDODSamp_Inst.NTamd64]`), then for the `.NT` decoration and finally for
+    /// the undecorated section.
+    pub fn install_section(&self, name: &str, platform: &Platform) -> Option<&InfSection> {
+        self.ddinstall(name, platform)
+            .map(|install| install.section)
+    }
+
+    /// Returns the DDInstall section for the platform together with its companions
+    ///
+    /// The DDInstall section is selected as in [`WinInfFile::install_section`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Arch, Platform, WinInfFile};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/AudioCodec.inf")).unwrap();
+    /// let install = inf_file
+    ///     .ddinstall("Audio_Device", &Platform::new(Arch::Amd64, 10, 0))
+    ///     .unwrap();
+    /// assert_eq!(install.decoration.as_deref(), Some("NT"));
+    /// assert_eq!(install.services.unwrap().name(), "Audio_Device.NT.Services");
+    /// assert!(install.hw.is_none());
+    /// ```
+    pub fn ddinstall(&self, name: &str, platform: &Platform) -> Option<InstallSection<'_>> {
+        let (decoration, section) = [
+            Some(format!("NT{}", platform.arch)),
+            Some("NT".to_string()),
+            None,
+        ]
+        .into_iter()
+        .find_map(|decoration| {
+            let section_name = match &decoration {
+                Some(decoration) => format!("{name}.{decoration}"),
+                None => name.to_string(),
+            };
+            self.section(&section_name)
+                .map(|section| (decoration, section))
+        })?;
+
+        let companion = |suffix: &str| self.section(&format!("{}.{suffix}", section.name()));
+        Some(InstallSection {
+            name: name.to_string(),
+            decoration,
+            section,
+            services: companion("Services"),
+            hw: companion("HW"),
+            coinstallers: companion("CoInstallers"),
+            interfaces: companion("Interfaces"),
+            wdf: companion("Wdf"),
+            components: companion("Components"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Arch;
+
+    #[test]
+    fn test_ddinstall_decoration_precedence() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Inst]\n[Inst.Services]\n",
+                "[Inst.NT]\n[Inst.NT.Services]\n[Inst.NT.HW]\n",
+                "[Inst.NTamd64]\n[Inst.NTamd64.Services]\n[Inst.NTamd64.CoInstallers]\n",
+            ))
+            .unwrap();
+
+        let install = inf_file
+            .ddinstall("inst", &Platform::new(Arch::Amd64, 10, 0))
+            .unwrap();
+        assert_eq!(install.section.name(), "Inst.NTamd64");
+        assert_eq!(install.decoration.as_deref(), Some("NTamd64"));
+        assert_eq!(install.services.unwrap().name(), "Inst.NTamd64.Services");
+        assert_eq!(
+            install.coinstallers.unwrap().name(),
+            "Inst.NTamd64.CoInstallers"
+        );
+        assert!(install.hw.is_none());
+        let companions: Vec<&str> = install.companions().map(|(suffix, _)| suffix).collect();
+        assert_eq!(companions, vec!["Services", "CoInstallers"]);
+
+        let install = inf_file
+            .ddinstall("Inst", &Platform::new(Arch::Arm64, 10, 0))
+            .unwrap();
+        assert_eq!(install.section.name(), "Inst.NT");
+        assert_eq!(install.hw.unwrap().name(), "Inst.NT.HW");
+
+        let mut inf_file = WinInfFile::default();
+        inf_file.parse_str("[Inst]\n[Inst.Services]\n").unwrap();
+        let install = inf_file
+            .ddinstall("Inst", &Platform::new(Arch::X86, 10, 0))
+            .unwrap();
+        assert_eq!(install.decoration, None);
+        assert_eq!(install.services.unwrap().name(), "Inst.Services");
+        assert!(
+            inf_file
+                .ddinstall("Missing", &Platform::new(Arch::X86, 10, 0))
+                .is_none()
+        );
+    }
+}
@@ -1,17 +1,66 @@
 #![allow(clippy::collapsible_if)]
-#![feature(str_from_utf16_endian)]
 
-use std::collections::HashMap;
 use std::fs::File;
-use std::io::{Error, Read};
+use std::io::{Error, ErrorKind, Read};
 use std::path::PathBuf;
 
-use encoding_rs::{Encoding, UTF_16LE};
+use encoding_rs::{Decoder, Encoding, UTF_8, UTF_16LE};
 use log::{debug, trace};
 
-pub use crate::types::{InfEntry, InfSection, InfValue};
-
+pub use crate::compliance::{
+    ComplianceLevel, ComplianceReport, ComplianceViolation, UNIVERSAL_DIRIDS,
+};
+pub use crate::cst::{DocumentError, InfDocument, LineEnding, LineKind, SyntaxLine};
+pub use crate::device::{DeviceMatch, ModelEntry};
+pub use crate::diagnostic::{Diagnostic, Severity};
+pub use crate::dirid::{Dirid, DiridError, DiridTable, PathStyle, SYSTEM_DIRIDS};
+pub use crate::driver_ver::{DriverDate, DriverVer, DriverVerError, DriverVersion};
+pub use crate::files::{DestinationDir, FileOperation, FileOperationKind, FilesError};
+pub use crate::graph::{SectionEdge, SectionGraph, SectionReference};
+pub use crate::install::InstallSection;
+pub use crate::lint::{LintContext, LintRule, Linter};
+pub use crate::manufacturer::{
+    Arch, Manufacturer, ManufacturerError, ModelsSection, Platform, TargetOsVersion,
+};
+pub use crate::package::{PackageError, PackageReport};
+pub use crate::registry::{
+    AddRegFlags, BitRegEntry, DelRegEntry, RegistryEntry, RegistryError, RegistryRoot,
+    RegistryValue,
+};
+pub use crate::service::{
+    ErrorControl, EventLogInstall, ServiceError, ServiceInstall, ServiceInstallFlags, ServiceType,
+    StartType,
+};
+pub use crate::source::{SourceDisk, SourceFile, SourceManifest, SourceMediaError};
+pub use crate::span::{Position, Span};
+pub use crate::strings::{StringTable, StringsError};
+pub use crate::types::{InfEntry, InfSection, InfSections, InfValue};
+pub use crate::version::{CatalogFile, Guid, GuidError, VersionError, VersionInfo};
+pub use crate::writer::{InfEncoding, WriteError, WriteOptions, escape_percent};
+
+use crate::fields::{FieldError, find_key_separator, split_fields, value_text};
+
+mod compliance;
+mod cst;
+mod device;
+mod diagnostic;
+mod dirid;
+mod driver_ver;
+mod fields;
+mod files;
+mod graph;
+mod install;
+mod lint;
+mod manufacturer;
+mod package;
+mod registry;
+mod service;
+mod source;
+mod span;
+mod strings;
 mod types;
+mod version;
+mod writer;
 
 /// Errors that can occur while parsing a Windows INF file
 #[derive(Debug, thiserror::Error)]
@@ -26,11 +75,34 @@ pub enum WinInfFileError {
     #[error("Failed to read file")]
     FileReadError,
     /// Failed to read a line from the file
-    #[error("Failed to read line: {0}")]
-    ReadLineError(#[from] LineReaderError),
+    #[error("Failed to read line at {span}: {source}")]
+    ReadLineError {
+        /// Why the line could not be read
+        source: LineReaderError,
+        /// Where the invalid characters are
+        span: Span,
+    },
     /// Failed to parse a section in the file
-    #[error("Failed to parse section: {0}")]
-    SectionParseError(#[from] SectionReaderError),
+    #[error("Failed to parse section at {span}: {source}")]
+    SectionParseError {
+        /// Why the line could not be parsed
+        source: SectionReaderError,
+        /// Where the line is, without leading and trailing whitespace
+        span: Span,
+    },
+}
+
+impl WinInfFileError {
+    /// Returns where in the content the error occurred
+    ///
+    /// Errors opening or reading the file have no span.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            WinInfFileError::ReadLineError { span, .. }
+            | WinInfFileError::SectionParseError { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
 }
 
 /// Errors that can occur while reading lines from a file
@@ -61,110 +133,238 @@ pub enum SectionReaderError {
 /// INF files are used for device driver installation and configuration in Windows.
 #[derive(Default)]
 pub struct WinInfFile {
-    /// The sections contained in the INF file
-    pub sections: HashMap<String, InfSection>,
+    /// The sections contained in the INF file, in file order
+    pub sections: InfSections,
     section_reader: SectionReader,
 }
 
-#[derive(Default)]
+/// Returns the number of bytes a character takes in the content as it was read
+fn encoded_len(c: char, utf16: bool) -> usize {
+    if utf16 {
+        c.len_utf16() * 2
+    } else {
+        c.len_utf8()
+    }
+}
+
+/// A line of the content, without its line ending
+struct Line {
+    text: String,
+    /// Where the first character of the line is
+    start: Position,
+    /// Whether the content was decoded from UTF-16
+    utf16: bool,
+}
+
+impl Line {
+    /// Returns the position of the character at byte index `idx` of the text
+    fn position(&self, idx: usize) -> Position {
+        self.text[..idx].chars().fold(self.start, |position, c| {
+            Position::new(
+                position.line,
+                position.column + 1,
+                position.offset + encoded_len(c, self.utf16),
+            )
+        })
+    }
+
+    /// Returns the span of the text without leading and trailing whitespace
+    fn span(&self) -> Span {
+        let trimmed = self.text.trim_start();
+        let start = self.text.len() - trimmed.len();
+        let end = start + trimmed.trim_end().len();
+        Span {
+            start: self.position(start),
+            end: self.position(end),
+        }
+    }
+}
+
 struct LineReader {
+    utf16: bool,
     pub remaining_string: String,
-    pub lines: Vec<String>,
+    /// Where the line in `remaining_string` starts
+    line_start: Position,
+    /// Where the next character is
+    next: Position,
+    /// Where a `\r` that still needs its `\n` is
+    cr: Option<Position>,
+    pub lines: Vec<Line>,
+    /// Reads a `\r` without `\n` as a line break instead of failing
+    recover: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Default for LineReader {
+    fn default() -> Self {
+        LineReader::new(false, 0)
+    }
 }
 
 impl LineReader {
-    fn read_to_line(&mut self, line_part: &str) -> Result<(), LineReaderError> {
-        let mut found_cr = false;
-        // pre-allocate memory for the new line at one shot and reuse it
-        let mut new_line = String::with_capacity(self.remaining_string.len() + line_part.len());
-        for c in self.remaining_string.chars().chain(line_part.chars()) {
+    /// Creates a reader for content whose first character is after a BOM of `bom_len` bytes
+    fn new(utf16: bool, bom_len: usize) -> Self {
+        let start = Position::new(1, 1, bom_len);
+        LineReader {
+            utf16,
+            remaining_string: String::new(),
+            line_start: start,
+            next: start,
+            cr: None,
+            lines: vec![],
+            recover: false,
+            diagnostics: vec![],
+        }
+    }
+
+    fn read_to_line(&mut self, line_part: &str) -> Result<(), WinInfFileError> {
+        for c in line_part.chars() {
             // If LF did not follow CR, fail
-            if found_cr && c != '\n' {
-                return Err(LineReaderError::InvalidCrlf(
-                    "found \\r but not \\n immediately".to_string(),
-                ));
-            }
-            // If CRLF encountered, read to line
-            if found_cr && c == '\n' {
-                if !new_line.is_empty() {
-                    self.lines.push(new_line.clone());
-                    new_line.clear();
+            if let Some(cr) = self.cr.take() {
+                if c != '\n' {
+                    let source = LineReaderError::InvalidCrlf(
+                        "found \\r but not \\n immediately".to_string(),
+                    );
+                    let span = Span {
+                        start: cr,
+                        end: Position::new(
+                            cr.line,
+                            cr.column + 1,
+                            cr.offset + encoded_len('\r', self.utf16),
+                        ),
+                    };
+                    if !self.recover {
+                        return Err(WinInfFileError::ReadLineError { source, span });
+                    }
+                    self.diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Error,
+                            "invalid-line-ending",
+                            format!("{source}, read as a line break"),
+                        )
+                        .with_span(span),
+                    );
+                    self.end_line();
                 }
-                found_cr = false;
-                continue;
             }
 
-            // If CR encountered, set flag
-            if c == '\r' {
-                found_cr = true;
-                continue;
+            let position = self.next;
+            self.next.column += 1;
+            self.next.offset += encoded_len(c, self.utf16);
+            match c {
+                // If CR encountered, wait for the LF
+                '\r' => self.cr = Some(position),
+                // If LF encountered, read to line
+                '\n' => self.end_line(),
+                // Add each char to the new line
+                c => self.remaining_string.push(c),
             }
+        }
+        Ok(())
+    }
 
-            // If \n encountered, read to line
-            if c == '\n' {
-                if !new_line.is_empty() {
-                    self.lines.push(new_line.clone());
-                    new_line.clear();
-                }
-                continue;
-            }
+    /// Ends the current line, the next character starts a new one
+    fn end_line(&mut self) {
+        self.push_line();
+        self.next.line += 1;
+        self.next.column = 1;
+        self.line_start = self.next;
+    }
 
-            // Add each char to the new line
-            new_line.push(c);
+    fn push_line(&mut self) {
+        if !self.remaining_string.is_empty() {
+            self.lines.push(Line {
+                text: std::mem::take(&mut self.remaining_string),
+                start: self.line_start,
+                utf16: self.utf16,
+            });
         }
-
-        // Add remaining line part without line ending back to the reader
-        self.remaining_string = new_line;
-        Ok(())
     }
 
-    fn take_lines(&mut self) -> Vec<String> {
+    fn take_lines(&mut self) -> Vec<Line> {
         self.lines.drain(0..).collect()
     }
 
     fn finalize(&mut self) {
-        if !self.remaining_string.is_empty() {
-            self.lines.push(self.remaining_string.clone());
-        }
+        self.push_line();
     }
 }
 
 #[derive(Default)]
 struct SectionReader {
     last_section_name: String,
-    last_entry_key: String,
-    last_entry_value_contd: String,
+    pending_entry: Option<PendingEntry>,
+    diagnostics: Vec<Diagnostic>,
+    /// Skips or repairs invalid lines instead of failing
+    recover: bool,
+}
+
+/// An entry whose value is continued on the next line
+struct PendingEntry {
+    key: Option<String>,
+    fields: Vec<String>,
+    /// The value as it is written on the lines read so far
+    raw: String,
+    /// The lines of the entry read so far
+    span: Span,
 }
 
 impl SectionReader {
     fn read_section(
         &mut self,
-        line: String,
-        sections: &mut HashMap<String, InfSection>,
+        line: Line,
+        sections: &mut InfSections,
     ) -> Result<(), SectionReaderError> {
+        let span = line.span();
         // trim spaces and tabs
-        let line = line.trim();
+        let line = line.text.trim();
 
-        // exclude comments
-        if line.starts_with(';') {
+        // exclude comments and blank lines
+        if line.starts_with(';') || (line.is_empty() && self.pending_entry.is_none()) {
             return Ok(());
         }
 
         // section name
         if line.starts_with('[') && line.ends_with(']') {
+            // a continuation right before a section header ends with the header
+            if let Some(pending) = self.pending_entry.take() {
+                self.push_entry(pending, sections);
+            }
+
             let section_name = line[1..line.len() - 1].to_string();
             if let Err(e) = validate_section_name(section_name.clone()) {
-                return Err(SectionReaderError::InvalidSectionName(e.to_string()));
+                let error = SectionReaderError::InvalidSectionName(e.to_string());
+                if !self.recover {
+                    return Err(error);
+                }
+                // entries up to the next valid header have no section to go to
+                self.recover_from(
+                    "invalid-section-name",
+                    error,
+                    "the section and its entries are skipped",
+                    span,
+                );
+                self.last_section_name.clear();
+                return Ok(());
             }
 
-            // TODO: if there are multiple sections with same name, we have to merge them
-            sections.insert(
-                section_name.clone(),
-                InfSection {
-                    name: section_name.clone(),
-                    entries: vec![],
-                },
-            );
+            // Windows concatenates sections with the same name, so entries of a
+            // repeated section are appended to the first occurrence
+            if sections.contains_key(&section_name) {
+                debug!("merging duplicate section: {section_name}");
+                self.diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Info,
+                        "duplicate-section",
+                        format!(
+                            "section [{section_name}] appears more than once, entries are merged"
+                        ),
+                    )
+                    .with_span(span),
+                );
+            } else {
+                sections.insert(InfSection::new(section_name.clone()).with_span(span));
+            }
             self.last_section_name = section_name.clone();
             return Ok(());
         }
@@ -175,112 +375,213 @@ impl SectionReader {
                 "processing entries for section name: {}",
                 self.last_section_name
             );
-            if let Some((key, value)) = line.split_once('=') {
-                let key = key.trim();
-                let mut value = value.trim();
-
-                if value.starts_with('"') {
-                    debug!("processing quoted value: {value}");
-                    let end_double_quote_idx = value[1..].find('"');
-                    if end_double_quote_idx.is_none() {
-                        return Err(SectionReaderError::InvalidQuotedValue(format!(
-                            "no ending double quote found, key: {}, section: {}",
-                            key, self.last_section_name
-                        )));
-                    }
-                    // +1 to include the first double quote
-                    let end_double_quote_idx = end_double_quote_idx.unwrap() + 1usize;
-                    // check for continuation char, -1 of length since its zero based
-                    if value.len() - 1usize > end_double_quote_idx {
-                        if let Some(c) = value.chars().nth(end_double_quote_idx + 1usize) {
-                            // check if the char after ending double quote is continuation char
-                            if c == '\\' {
-                                self.last_entry_key = key.to_string();
-                                self.last_entry_value_contd =
-                                    value[1..end_double_quote_idx].to_string();
-                                return Ok(());
-                            } else if c != ';' {
-                                // TODO: if there are any chars after ending double quote apart from continuation char or comment, should we consider this as malformed value?
-                                return Err(SectionReaderError::InvalidContinuation(format!(
-                                    "Invalid INF entry value: {}, no continuation char found after ending double quote, key: {}, section_name: {}",
-                                    value, key, self.last_section_name
-                                )));
-                            }
-                        }
-                    }
 
-                    self.last_entry_value_contd.clear();
-                    // exclude double quotes
-                    value = &value[1..end_double_quote_idx];
-                    if let Some(section) = sections.get_mut(&self.last_section_name) {
-                        let new_entry = InfEntry::KeyValue(
-                            key.to_string(),
-                            Some(InfValue::Raw(value.to_string())),
-                        );
-                        section.entries.push(new_entry);
-                    }
-                } else {
-                    debug!("processing unquoted value: {value}");
-
-                    // value containing comments at the end
-                    if let Some((first, _)) = value.split_once(';') {
-                        value = first.trim();
-                    }
-
-                    // multiple backslashes at the end, windows treat only the last one as line continuator and ignores rest
-                    if value.ends_with('\\') {
-                        debug!("processing unquoted contd value: {value}");
-                        if let Some(first_backslash_idx) = value.find('\\') {
-                            if first_backslash_idx > 0 {
-                                self.last_entry_value_contd =
-                                    value[..first_backslash_idx].to_string();
-                                self.last_entry_key = key.to_string();
-                                return Ok(());
-                            } else {
-                                self.last_entry_value_contd.clear();
-                                self.last_entry_key.clear();
-                                return Ok(());
-                            }
-                        }
+            // a line following a continuation backslash carries on the previous entry
+            let (key, value, mut fields, mut raw, entry_span) = match self.pending_entry.take() {
+                Some(pending) => {
+                    debug!("processing contd value: {line}");
+                    // a blank line ends the entry without adding to it
+                    let end = if line.is_empty() {
+                        pending.span.end
                     } else {
-                        debug!("processing unquoted non contd value: {value}");
-                        self.last_entry_value_contd.clear();
-                        self.last_entry_key.clear();
-
-                        if let Some(entry) = sections.get_mut(&self.last_section_name) {
-                            entry.entries.push(InfEntry::KeyValue(
-                                key.to_string(),
-                                Some(InfValue::Raw(value.to_string())),
+                        span.end
+                    };
+                    let entry_span = Span {
+                        start: pending.span.start,
+                        end,
+                    };
+                    (pending.key, line, pending.fields, pending.raw, entry_span)
+                }
+                None => match find_key_separator(line) {
+                    Some(idx) => (
+                        Some(line[..idx].trim().to_string()),
+                        line[idx + 1..].trim(),
+                        vec![],
+                        String::new(),
+                        span,
+                    ),
+                    None => (None, line, vec![], String::new(), span),
+                },
+            };
+
+            debug!("processing value: {value}");
+            let previous_fields = fields.clone();
+            let continued = match split_fields(value, &mut fields) {
+                Ok(continued) => continued,
+                Err(e) => {
+                    let error = match e {
+                        FieldError::UnterminatedQuote => {
+                            SectionReaderError::InvalidQuotedValue(format!(
+                                "no ending double quote found, key: {}, section: {}",
+                                key.as_deref().unwrap_or_default(),
+                                self.last_section_name
+                            ))
+                        }
+                        FieldError::TextAfterQuote => {
+                            SectionReaderError::InvalidContinuation(format!(
+                                "Invalid INF entry value: {}, no continuation char found after ending double quote, key: {}, section_name: {}",
+                                value,
+                                key.as_deref().unwrap_or_default(),
+                                self.last_section_name
                             ))
                         }
+                    };
+                    if !self.recover {
+                        return Err(error);
                     }
-                }
-            } else {
-                let value = line.trim();
-
-                // TODO: what if there are multiple continuation lines?
-                if !self.last_entry_value_contd.is_empty() {
-                    if let Some(entry) = sections.get_mut(&self.last_section_name) {
-                        self.last_entry_value_contd.push_str(value);
-                        entry.entries.push(InfEntry::KeyValue(
-                            self.last_entry_key.to_string(),
-                            Some(InfValue::Raw(self.last_entry_value_contd.clone())),
-                        ));
+
+                    // a quote left open is closed at the end of the line, any other
+                    // invalid value drops the entry
+                    fields = previous_fields;
+                    let repaired = match e {
+                        FieldError::UnterminatedQuote => {
+                            split_fields(&format!("{value}\""), &mut fields).ok()
+                        }
+                        FieldError::TextAfterQuote => None,
+                    };
+                    match repaired {
+                        Some(continued) => {
+                            self.recover_from(
+                                "unterminated-quote",
+                                error,
+                                "the quote is closed at the end of the line",
+                                span,
+                            );
+                            continued
+                        }
+                        None => {
+                            self.recover_from(
+                                "text-after-quote",
+                                error,
+                                "the entry is skipped",
+                                span,
+                            );
+                            return Ok(());
+                        }
                     }
-                    self.last_entry_value_contd.clear();
-                    self.last_entry_key.clear();
-                } else if let Some(entry) = sections.get_mut(&self.last_section_name) {
-                    entry
-                        .entries
-                        .push(InfEntry::OnlyValue(InfValue::Raw(value.to_string())));
                 }
+            };
+
+            raw.push_str(value_text(value, continued));
+            let pending = PendingEntry {
+                key,
+                fields,
+                raw,
+                span: entry_span,
+            };
+            if continued {
+                self.pending_entry = Some(pending);
+                return Ok(());
             }
+
+            self.push_entry(pending, sections);
         }
         Ok(())
     }
+
+    /// Records an error that was skipped or repaired in recovering mode
+    fn recover_from(
+        &mut self,
+        code: &'static str,
+        error: SectionReaderError,
+        action: &str,
+        span: Span,
+    ) {
+        self.diagnostics.push(
+            Diagnostic::new(Severity::Error, code, format!("{error}, {action}")).with_span(span),
+        );
+    }
+
+    fn push_entry(&self, entry: PendingEntry, sections: &mut InfSections) {
+        let value = InfValue::from_fields(entry.fields);
+        if let Some(section) = sections.get_mut(&self.last_section_name) {
+            let parsed = match entry.key {
+                Some(key) => InfEntry::KeyValue(key, Some(value)),
+                None => InfEntry::OnlyValue(value),
+            };
+            section.push_parsed_entry(parsed, entry.span, entry.raw);
+        }
+    }
 }
 
 impl WinInfFile {
+    /// Creates a parser that recovers from invalid lines instead of failing
+    ///
+    /// Its `parse` methods skip or repair each invalid line, record a [`Severity::Error`]
+    /// diagnostic with the span of the line and carry on, so the sections read so far and
+    /// all problems of the file are available afterwards. Only failures to open or read
+    /// the content are still returned as errors.
+    ///
+    /// | Code | Problem | Recovery |
+    /// |------|---------|----------|
+    /// | `invalid-line-ending` | `\r` without `\n` | read as a line break |
+    /// | `invalid-section-name` | invalid section header | section and its entries skipped |
+    /// | `unterminated-quote` | quoted field without closing quote | quote closed at end of line |
+    /// | `text-after-quote` | text after a closing quote | entry skipped |
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Severity, WinInfFile};
+    ///
+    /// let mut inf_file = WinInfFile::recovering();
+    /// inf_file
+    ///     .parse_str("[Bad Name]\nA=1\n[Strings]\nB=\"open\nC=2\n")
+    ///     .unwrap();
+    /// assert!(inf_file.section("Strings").unwrap().get("C").is_some());
+    /// let codes: Vec<_> = inf_file.diagnostics().iter().map(|d| d.code).collect();
+    /// assert_eq!(codes, ["invalid-section-name", "unterminated-quote"]);
+    /// assert!(inf_file.has_errors());
+    /// ```
+    pub fn recovering() -> Self {
+        WinInfFile {
+            section_reader: SectionReader {
+                recover: true,
+                ..SectionReader::default()
+            },
+            ..WinInfFile::default()
+        }
+    }
+
+    /// Returns the diagnostics collected while parsing, in file order
+    ///
+    /// These describe constructs that were accepted but may not be what the INF author
+    /// intended, such as a section header that appears more than once, and in
+    /// [recovering mode](WinInfFile::recovering) the invalid lines that were skipped or
+    /// repaired.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.section_reader.diagnostics
+    }
+
+    /// Returns `true` if an error diagnostic was recorded while parsing
+    ///
+    /// This only happens in [recovering mode](WinInfFile::recovering).
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics()
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// Returns the section with the given name
+    ///
+    /// Section names are matched ignoring case, as Windows does, so `[version]` and
+    /// `[Version]` refer to the same section.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse_str("[Version]\nPnpLockdown=1\n").unwrap();
+    /// let version = inf_file.section("VERSION").unwrap();
+    /// assert_eq!(version.name(), "Version");
+    /// assert!(version.get("pnplockdown").is_some());
+    /// ```
+    pub fn section(&self, name: &str) -> Option<&InfSection> {
+        self.sections.get(name)
+    }
+
     /// Parse a Windows INF file from the given path
     ///
     /// # Arguments
@@ -307,81 +608,129 @@ impl WinInfFile {
             return Err(WinInfFileError::FileDoNotExist);
         }
 
-        let mut f = File::open(file_path)?;
-        let mut decoder = UTF_16LE.new_decoder();
+        let f = File::open(file_path)?;
+        self.parse_reader(f)
+    }
 
-        let mut line_reader = LineReader::default();
+    /// Parse Windows INF content held in memory
+    ///
+    /// The bytes go through the same BOM detection as [`WinInfFile::parse`], so both
+    /// UTF-8 and UTF-16LE (with BOM) content is accepted. This is useful for INF files
+    /// extracted from driver packages or embedded with `include_bytes!`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse_bytes(b"[Version]\r\nSignature=\"$Windows NT$\"\r\n").unwrap();
+    /// assert!(inf_file.sections.get("Version").is_some());
+    /// ```
+    pub fn parse_bytes(&mut self, bytes: &[u8]) -> Result<(), WinInfFileError> {
+        self.parse_reader(bytes)
+    }
+
+    /// Parse Windows INF content from a string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse_str("[Strings]\nProviderName = \"Contoso\"\n").unwrap();
+    /// assert_eq!(inf_file.sections.get("Strings").unwrap().entries().len(), 1);
+    /// ```
+    pub fn parse_str(&mut self, content: &str) -> Result<(), WinInfFileError> {
+        self.parse_bytes(content.as_bytes())
+    }
+
+    /// Parse Windows INF content from any [`Read`] source
+    ///
+    /// The content is read in chunks, decoded as UTF-16LE when a BOM is found at the
+    /// start of the stream and as UTF-8 otherwise, and then split into lines and sections.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source to read the INF content from
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the content was parsed successfully
+    /// * `Err(WinInfFileError)` if an error occurred during reading or parsing
+    pub fn parse_reader<R: Read>(&mut self, mut reader: R) -> Result<(), WinInfFileError> {
+        let mut decoder: Option<Decoder> = None;
+        let recover = self.section_reader.recover;
+        let mut line_reader = LineReader {
+            recover,
+            ..LineReader::default()
+        };
+        let first_diagnostic = self.section_reader.diagnostics.len();
 
         let buf_size = 1024;
-        let mut bom_detected = false;
+        let mut buf: Vec<u8> = vec![0; buf_size];
         loop {
-            let mut buf: Vec<u8> = vec![0; buf_size];
-            let read_count = f.read(&mut buf);
-            if read_count.is_err() {
-                return Err(WinInfFileError::FileReadError);
-            }
-            let read_count = read_count.unwrap();
-            if read_count == 0 {
+            let read_count = match reader.read(&mut buf) {
+                Ok(read_count) => read_count,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return Err(WinInfFileError::FileReadError),
+            };
+            let last = read_count == 0;
+            if last {
                 trace!("bytes read: {read_count}");
-                break;
             }
 
-            // This properly prints the BOM value for the first buf
+            // The BOM, if any, can only be at the very start of the stream
             // Bom data: (Encoding { UTF-16LE }, 2)
-            let bom = Encoding::for_bom(&buf[..read_count]);
-            if let Some(b) = bom {
-                debug!("Bom data: {b:?}");
-                bom_detected = true;
-            }
-
-            if bom_detected {
-                // This works perfectly for UTF16 LE
-                // Ref: https://learn.microsoft.com/en-us/windows-hardware/drivers/display/general-unicode-requirement
-                let mut utf16_buf = vec![0; buf_size / 2];
-                let res = decoder.decode_to_utf16(
-                    &buf[..read_count],
-                    &mut utf16_buf,
-                    read_count != buf_size,
-                );
-                debug!(
-                    "decoded chars: {:?}",
-                    String::from_utf16_lossy(&utf16_buf[..res.2])
-                );
-                if let Err(e) =
-                    line_reader.read_to_line(&String::from_utf16_lossy(&utf16_buf[..res.2]))
-                {
-                    return Err(WinInfFileError::ReadLineError(e));
-                }
-            } else {
-                debug!(
-                    "decoded chars: {:?}",
-                    String::from_utf8_lossy(&buf[..read_count])
-                );
-                if let Err(e) =
-                    line_reader.read_to_line(String::from_utf8_lossy(&buf[..read_count]).as_ref())
-                {
-                    return Err(WinInfFileError::ReadLineError(e));
+            let decoder = decoder.get_or_insert_with(|| {
+                if let Some((encoding, bom_len)) = Encoding::for_bom(&buf[..read_count]) {
+                    debug!("Bom data: {:?}", (encoding, bom_len));
+                    // positions start after the BOM and count UTF-16 code units as two bytes
+                    line_reader = LineReader {
+                        recover,
+                        ..LineReader::new(encoding != UTF_8, bom_len)
+                    };
+                    // This works perfectly for UTF16 LE
+                    // Ref: https://learn.microsoft.com/en-us/windows-hardware/drivers/display/general-unicode-requirement
+                    UTF_16LE.new_decoder()
+                } else {
+                    UTF_8.new_decoder()
                 }
-            }
+            });
+
+            // decoders keep partial characters between chunks, so a code unit split
+            // across two reads is decoded correctly
+            let mut decoded = String::with_capacity(
+                decoder
+                    .max_utf8_buffer_length(read_count)
+                    .unwrap_or(read_count * 3),
+            );
+            let _ = decoder.decode_to_string(&buf[..read_count], &mut decoded, last);
+            debug!("decoded chars: {decoded:?}");
+
+            line_reader.read_to_line(&decoded)?;
+            self.section_reader
+                .diagnostics
+                .append(&mut line_reader.diagnostics);
 
             for line in line_reader.take_lines() {
-                if let Err(e) = self.section_reader.read_section(line, &mut self.sections) {
-                    return Err(WinInfFileError::SectionParseError(e));
-                }
+                self.read_line(line)?;
+            }
+
+            if last {
+                break;
             }
         }
 
         line_reader.finalize();
         for line in line_reader.take_lines() {
-            if let Err(e) = self.section_reader.read_section(line, &mut self.sections) {
-                return Err(WinInfFileError::SectionParseError(e));
-            }
+            self.read_line(line)?;
         }
 
-        debug!("total lines: {}", line_reader.lines.len());
-        for line in line_reader.lines.iter() {
-            debug!(">> line: {line}");
-        }
+        // line endings are checked a chunk ahead of the sections
+        self.section_reader.diagnostics[first_diagnostic..]
+            .sort_by_key(|diagnostic| diagnostic.span.map(|span| span.start.offset));
 
         for (section_name, section) in self.sections.iter() {
             debug!(">> section name: {section_name}, section: {section:?}");
@@ -389,6 +738,13 @@ impl WinInfFile {
 
         Ok(())
     }
+
+    fn read_line(&mut self, line: Line) -> Result<(), WinInfFileError> {
+        let span = line.span();
+        self.section_reader
+            .read_section(line, &mut self.sections)
+            .map_err(|source| WinInfFileError::SectionParseError { source, span })
+    }
 }
 
 fn validate_section_name<'a>(name: String) -> Result<(), &'a str> {
@@ -437,11 +793,25 @@ fn validate_section_name<'a>(name: String) -> Result<(), &'a str> {
 mod tests {
     use super::*;
 
+    impl From<&str> for Line {
+        fn from(text: &str) -> Self {
+            Line {
+                text: text.to_string(),
+                start: Position::default(),
+                utf16: false,
+            }
+        }
+    }
+
+    fn texts(lines: Vec<Line>) -> Vec<String> {
+        lines.into_iter().map(|line| line.text).collect()
+    }
+
     #[test]
     fn test_line_reader_basic() {
         let mut reader = LineReader::default();
         assert!(reader.read_to_line("Hello\r\nWorld\r\n").is_ok());
-        let lines = reader.take_lines();
+        let lines = texts(reader.take_lines());
         assert_eq!(lines, vec!["Hello", "World"]);
     }
 
@@ -449,7 +819,7 @@ mod tests {
     fn test_line_reader_incomplete_line() {
         let mut reader = LineReader::default();
         assert!(reader.read_to_line("Hello\r\nWor").is_ok());
-        let lines = reader.take_lines();
+        let lines = texts(reader.take_lines());
         assert_eq!(lines, vec!["Hello"]);
         assert_eq!(reader.remaining_string, "Wor");
     }
@@ -465,19 +835,39 @@ mod tests {
         let mut reader = LineReader::default();
         assert!(reader.read_to_line("Hello\r\nWorld").is_ok());
         reader.finalize();
-        let lines = reader.take_lines();
+        let lines = texts(reader.take_lines());
         assert_eq!(lines, vec!["Hello", "World"]);
     }
 
+    #[test]
+    fn test_line_reader_positions() {
+        let mut reader = LineReader::new(true, 2);
+        assert!(reader.read_to_line("Hé\r\n\n  World\n").is_ok());
+        let lines = reader.take_lines();
+        assert_eq!(lines[0].start, Position::new(1, 1, 2));
+        assert_eq!(lines[1].start, Position::new(3, 1, 12));
+        assert_eq!(
+            lines[1].span(),
+            Span {
+                start: Position::new(3, 3, 16),
+                end: Position::new(3, 8, 26),
+            }
+        );
+
+        let mut reader = LineReader::default();
+        let err = reader.read_to_line("ok\n€\rx").unwrap_err();
+        assert_eq!(err.span().unwrap().start, Position::new(2, 2, 6));
+    }
+
     #[test]
     fn test_section_reader_basic() {
         let mut reader = SectionReader::default();
-        let mut sections = HashMap::new();
+        let mut sections = InfSections::default();
 
         // Test section header
         assert!(
             reader
-                .read_section("[TestSection]".to_string(), &mut sections)
+                .read_section("[TestSection]".into(), &mut sections)
                 .is_ok()
         );
         assert_eq!(reader.last_section_name, "TestSection");
@@ -486,12 +876,12 @@ mod tests {
         // Test key-value pair
         assert!(
             reader
-                .read_section("key=value".to_string(), &mut sections)
+                .read_section("key=value".into(), &mut sections)
                 .is_ok()
         );
         let section = sections.get("TestSection").unwrap();
-        assert_eq!(section.entries.len(), 1);
-        if let InfEntry::KeyValue(key, value) = &section.entries[0] {
+        assert_eq!(section.entries().len(), 1);
+        if let InfEntry::KeyValue(key, value) = &section.entries()[0] {
             assert_eq!(key, "key");
             assert_eq!(value.as_ref().unwrap(), &InfValue::Raw("value".to_string()));
         } else {
@@ -502,22 +892,22 @@ mod tests {
     #[test]
     fn test_section_reader_quoted_value() {
         let mut reader = SectionReader::default();
-        let mut sections = HashMap::new();
+        let mut sections = InfSections::default();
 
         assert!(
             reader
-                .read_section("[TestSection]".to_string(), &mut sections)
+                .read_section("[TestSection]".into(), &mut sections)
                 .is_ok()
         );
         assert!(
             reader
-                .read_section("key=\"quoted value\"".to_string(), &mut sections)
+                .read_section("key=\"quoted value\"".into(), &mut sections)
                 .is_ok()
         );
 
         let section = sections.get("TestSection").unwrap();
-        assert_eq!(section.entries.len(), 1);
-        if let InfEntry::KeyValue(key, value) = &section.entries[0] {
+        assert_eq!(section.entries().len(), 1);
+        if let InfEntry::KeyValue(key, value) = &section.entries()[0] {
             assert_eq!(key, "key");
             assert_eq!(
                 value.as_ref().unwrap(),
@@ -531,29 +921,30 @@ mod tests {
     #[test]
     fn test_section_reader_quoted_value_with_continuation() {
         let mut reader = SectionReader::default();
-        let mut sections = HashMap::new();
+        let mut sections = InfSections::default();
 
         assert!(
             reader
-                .read_section("[TestSection]".to_string(), &mut sections)
+                .read_section("[TestSection]".into(), &mut sections)
                 .is_ok()
         );
         assert!(
             reader
-                .read_section("key=\"quoted value\\\"\\".to_string(), &mut sections)
+                .read_section("key=\"quoted value\\\"\\".into(), &mut sections)
                 .is_ok()
         );
-        assert_eq!(reader.last_entry_value_contd, "quoted value\\");
-        assert_eq!(reader.last_entry_key, "key");
+        let pending = reader.pending_entry.as_ref().unwrap();
+        assert_eq!(pending.fields, vec!["quoted value\\"]);
+        assert_eq!(pending.key.as_deref(), Some("key"));
 
         assert!(
             reader
-                .read_section("  continued value  ".to_string(), &mut sections)
+                .read_section("  continued value  ".into(), &mut sections)
                 .is_ok()
         );
         let section = sections.get("TestSection").unwrap();
-        assert_eq!(section.entries.len(), 1);
-        if let InfEntry::KeyValue(key, value) = &section.entries[0] {
+        assert_eq!(section.entries().len(), 1);
+        if let InfEntry::KeyValue(key, value) = &section.entries()[0] {
             assert_eq!(key, "key");
             assert_eq!(
                 value.as_ref().unwrap(),
@@ -567,28 +958,28 @@ mod tests {
     #[test]
     fn test_section_reader_continued_value() {
         let mut reader = SectionReader::default();
-        let mut sections = HashMap::new();
+        let mut sections = InfSections::default();
 
         assert!(
             reader
-                .read_section("[TestSection]".to_string(), &mut sections)
+                .read_section("[TestSection]".into(), &mut sections)
                 .is_ok()
         );
         assert!(
             reader
-                .read_section("key=value\\".to_string(), &mut sections)
+                .read_section("key=value\\".into(), &mut sections)
                 .is_ok()
         );
-        assert_eq!(reader.last_entry_value_contd, "value");
+        assert_eq!(reader.pending_entry.as_ref().unwrap().fields, vec!["value"]);
 
         assert!(
             reader
-                .read_section("continued".to_string(), &mut sections)
+                .read_section("continued".into(), &mut sections)
                 .is_ok()
         );
         let section = sections.get("TestSection").unwrap();
-        assert_eq!(section.entries.len(), 1);
-        if let InfEntry::KeyValue(key, value) = &section.entries[0] {
+        assert_eq!(section.entries().len(), 1);
+        if let InfEntry::KeyValue(key, value) = &section.entries()[0] {
             assert_eq!(key, "key");
             assert_eq!(
                 value.as_ref().unwrap(),
@@ -602,27 +993,27 @@ mod tests {
     #[test]
     fn test_section_reader_comments() {
         let mut reader = SectionReader::default();
-        let mut sections = HashMap::new();
+        let mut sections = InfSections::default();
 
         assert!(
             reader
-                .read_section("[TestSection]".to_string(), &mut sections)
+                .read_section("[TestSection]".into(), &mut sections)
                 .is_ok()
         );
         assert!(
             reader
-                .read_section("; This is a comment".to_string(), &mut sections)
+                .read_section("; This is a comment".into(), &mut sections)
                 .is_ok()
         );
         assert!(
             reader
-                .read_section("key=value ; This is a comment".to_string(), &mut sections)
+                .read_section("key=value ; This is a comment".into(), &mut sections)
                 .is_ok()
         );
 
         let section = sections.get("TestSection").unwrap();
-        assert_eq!(section.entries.len(), 1);
-        if let InfEntry::KeyValue(key, value) = &section.entries[0] {
+        assert_eq!(section.entries().len(), 1);
+        if let InfEntry::KeyValue(key, value) = &section.entries()[0] {
             assert_eq!(key, "key");
             assert_eq!(value.as_ref().unwrap(), &InfValue::Raw("value".to_string()));
         } else {
@@ -630,19 +1021,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_section_reader_duplicate_sections_merged() {
+        let mut reader = SectionReader::default();
+        let mut sections = InfSections::default();
+
+        for line in ["[First]", "a=1", "[Second]", "b=2", "[First]", "c=3"] {
+            assert!(reader.read_section(line.into(), &mut sections).is_ok());
+        }
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections.index_of("First"), Some(0));
+        let section = sections.get("First").unwrap();
+        assert_eq!(
+            section.entries(),
+            vec![
+                InfEntry::KeyValue("a".to_string(), Some(InfValue::Raw("1".to_string()))),
+                InfEntry::KeyValue("c".to_string(), Some(InfValue::Raw("3".to_string()))),
+            ]
+        );
+        assert_eq!(reader.diagnostics.len(), 1);
+        assert_eq!(reader.diagnostics[0].code, "duplicate-section");
+        assert!(reader.diagnostics[0].span.is_some());
+    }
+
+    #[test]
+    fn test_section_reader_recover() {
+        let mut reader = SectionReader {
+            recover: true,
+            ..SectionReader::default()
+        };
+        let mut sections = InfSections::default();
+
+        for line in [
+            "[Good]",
+            "a=\"x\"y",
+            "b=1,\\",
+            "\"two",
+            "[Bad Name]",
+            "c=3",
+            "[Good]",
+            "d=4",
+        ] {
+            assert!(reader.read_section(line.into(), &mut sections).is_ok());
+        }
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(
+            sections.get("Good").unwrap().entries(),
+            vec![
+                InfEntry::KeyValue(
+                    "b".to_string(),
+                    Some(InfValue::CommaSeparated(vec![
+                        "1".to_string(),
+                        "two".to_string()
+                    ]))
+                ),
+                InfEntry::KeyValue("d".to_string(), Some(InfValue::Raw("4".to_string()))),
+            ]
+        );
+        let codes: Vec<_> = reader.diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(
+            codes,
+            [
+                "text-after-quote",
+                "unterminated-quote",
+                "invalid-section-name",
+                "duplicate-section"
+            ]
+        );
+        assert_eq!(reader.diagnostics[0].severity, Severity::Error);
+    }
+
     #[test]
     fn test_section_reader_invalid_section() {
         let mut reader = SectionReader::default();
-        let mut sections = HashMap::new();
+        let mut sections = InfSections::default();
 
         assert!(
             reader
-                .read_section("[Invalid Section]".to_string(), &mut sections)
+                .read_section("[Invalid Section]".into(), &mut sections)
                 .is_err()
         );
         assert!(
             reader
-                .read_section("[Section with \\]".to_string(), &mut sections)
+                .read_section("[Section with \\]".into(), &mut sections)
                 .is_err()
         );
     }
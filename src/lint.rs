@@ -0,0 +1,529 @@
+use std::collections::HashSet;
+
+use crate::compliance::COMPLIANCE_CODES;
+use crate::graph::base_name;
+use crate::{
+    ComplianceLevel, ComplianceViolation, Diagnostic, InfSection, Severity, Span, StringTable,
+    WinInfFile,
+};
+
+/// What a [`LintRule`] checks
+pub struct LintContext<'a> {
+    /// The INF file being checked
+    pub inf_file: &'a WinInfFile,
+    /// The string tokens of the `[Strings]` section
+    pub strings: StringTable,
+    /// The driver model the file is checked against
+    pub level: ComplianceLevel,
+    /// The [compliance violations](WinInfFile::compliance_violations) of the file, empty
+    /// for [`ComplianceLevel::Legacy`]
+    pub violations: Vec<ComplianceViolation>,
+}
+
+/// A check run by a [`Linter`]
+///
+/// # Examples
+///
+/// ```
+/// use inf_rs::{ComplianceLevel, Diagnostic, LintContext, LintRule, Linter, Severity, WinInfFile};
+///
+/// struct NoClass;
+///
+/// impl LintRule for NoClass {
+///     fn code(&self) -> &'static str {
+///         "missing-class"
+///     }
+///
+///     fn description(&self) -> &'static str {
+///         "[Version] has no Class directive"
+///     }
+///
+///     fn check(&self, context: &LintContext<'_>) -> Vec<Diagnostic> {
+///         match context.inf_file.section("Version") {
+///             Some(version) if version.get("Class").is_none() => vec![Diagnostic::new(
+///                 Severity::Warning,
+///                 self.code(),
+///                 "[Version] has no Class directive".to_string(),
+///             )],
+///             _ => vec![],
+///         }
+///     }
+/// }
+///
+/// let mut inf_file = WinInfFile::default();
+/// inf_file.parse_str("[Version]\nPnpLockdown = 1\nCatalogFile = a.cat\n").unwrap();
+/// let mut linter = Linter::new(ComplianceLevel::Legacy);
+/// linter.add_rule(NoClass);
+/// let codes: Vec<_> = linter.run(&inf_file).iter().map(|d| d.code).collect();
+/// assert_eq!(codes, ["missing-class"]);
+/// ```
+pub trait LintRule {
+    /// The code of the diagnostics the rule reports, e.g. `missing-pnp-lockdown`
+    fn code(&self) -> &'static str;
+
+    /// A short description of what the rule checks
+    fn description(&self) -> &'static str;
+
+    /// Returns a diagnostic for each problem found
+    fn check(&self, context: &LintContext<'_>) -> Vec<Diagnostic>;
+}
+
+/// Runs a catalog of [`LintRule`]s over an INF file
+///
+/// A new linter has the built-in rules, all enabled:
+///
+/// | Code | Level | Checks |
+/// |------|-------|--------|
+/// | `missing-pnp-lockdown` | all | `[Version]` sets `PnpLockdown = 1` |
+/// | `missing-catalog-file` | all | `[Version]` has a `CatalogFile` directive |
+/// | `undefined-string` | all | every `%strkey%` token is defined in a `[Strings]` section |
+/// | `unreferenced-section` | all | every section is read by Windows or referenced |
+/// | `disallowed-directive` | universal | no legacy directives such as `LogConfig` |
+/// | `disallowed-section` | universal | no `.CoInstallers`, `.FactDef` or `.LogConfigOverride` sections |
+/// | `disallowed-registry-root` | universal | registry lines only write the allowed roots |
+/// | `disallowed-dirid` | universal | `[DestinationDirs]` only uses [`crate::UNIVERSAL_DIRIDS`] |
+/// | `copyfiles-system32` | declarative | `CopyFiles` does not copy to System32 |
+///
+/// The rules with a level check the [compliance violations](WinInfFile::compliance_violations)
+/// of that level and are skipped for [`ComplianceLevel::Legacy`].
+pub struct Linter {
+    level: ComplianceLevel,
+    rules: Vec<Box<dyn LintRule>>,
+    disabled: HashSet<String>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new(ComplianceLevel::default())
+    }
+}
+
+impl Linter {
+    /// Creates a linter with the built-in rules for the level
+    pub fn new(level: ComplianceLevel) -> Self {
+        let mut rules: Vec<Box<dyn LintRule>> = vec![
+            Box::new(MissingPnpLockdown),
+            Box::new(MissingCatalogFile),
+            Box::new(UndefinedString),
+            Box::new(UnreferencedSection),
+        ];
+        rules.extend(COMPLIANCE_CODES.iter().map(|(code, description)| {
+            Box::new(ComplianceRule { code, description }) as Box<dyn LintRule>
+        }));
+        Linter {
+            level,
+            rules,
+            disabled: HashSet::new(),
+        }
+    }
+
+    /// Returns the level the rules check against
+    pub fn level(&self) -> ComplianceLevel {
+        self.level
+    }
+
+    /// Adds a rule to the catalog, enabled
+    pub fn add_rule(&mut self, rule: impl LintRule + 'static) {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Iterates over the rules of the catalog, enabled or not
+    pub fn rules(&self) -> impl Iterator<Item = &dyn LintRule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    /// Enables the rule with the given code
+    pub fn enable(&mut self, code: &str) {
+        self.disabled.remove(code);
+    }
+
+    /// Disables the rule with the given code
+    pub fn disable(&mut self, code: &str) {
+        self.disabled.insert(code.to_string());
+    }
+
+    /// Returns `true` if the rule with the given code is not disabled
+    pub fn is_enabled(&self, code: &str) -> bool {
+        !self.disabled.contains(code)
+    }
+
+    /// Runs the enabled rules and returns their diagnostics
+    ///
+    /// Diagnostics tied to a place come in file order after those about the file as a
+    /// whole.
+    pub fn run(&self, inf_file: &WinInfFile) -> Vec<Diagnostic> {
+        let violations = match self.level {
+            ComplianceLevel::Legacy => vec![],
+            _ => inf_file.compliance_violations(),
+        };
+        let context = LintContext {
+            inf_file,
+            strings: inf_file.string_table(),
+            level: self.level,
+            violations,
+        };
+        let mut diagnostics: Vec<Diagnostic> = self
+            .rules()
+            .filter(|rule| self.is_enabled(rule.code()))
+            .flat_map(|rule| rule.check(&context))
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.map(|span| span.start.offset));
+        diagnostics
+    }
+}
+
+impl WinInfFile {
+    /// Checks the INF file with the built-in lint rules for the level
+    ///
+    /// Use a [`Linter`] to disable rules or to add your own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{ComplianceLevel, WinInfFile};
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file
+    ///     .parse_str("[Version]\nCatalogFile = a.cat\n[DestinationDirs]\nDefaultDestDir = 10\n")
+    ///     .unwrap();
+    /// let codes: Vec<_> = inf_file
+    ///     .lint(ComplianceLevel::Universal)
+    ///     .iter()
+    ///     .map(|d| d.code)
+    ///     .collect();
+    /// assert_eq!(codes, ["missing-pnp-lockdown", "disallowed-dirid"]);
+    /// ```
+    pub fn lint(&self, level: ComplianceLevel) -> Vec<Diagnostic> {
+        Linter::new(level).run(self)
+    }
+}
+
+fn report(
+    code: &'static str,
+    severity: Severity,
+    message: String,
+    span: Option<Span>,
+) -> Diagnostic {
+    let diagnostic = Diagnostic::new(severity, code, message);
+    match span {
+        Some(span) => diagnostic.with_span(span),
+        None => diagnostic,
+    }
+}
+
+struct MissingPnpLockdown;
+
+impl LintRule for MissingPnpLockdown {
+    fn code(&self) -> &'static str {
+        "missing-pnp-lockdown"
+    }
+
+    fn description(&self) -> &'static str {
+        "[Version] sets PnpLockdown = 1 so that installed files cannot be replaced"
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<Diagnostic> {
+        // a file without [Version] is reported as a whole
+        let version = context.inf_file.section("Version");
+        let directive = version.and_then(|version| {
+            version
+                .entries_with_spans()
+                .find(|(entry, _)| entry.has_key("PnpLockdown"))
+        });
+        match directive {
+            None => vec![report(
+                self.code(),
+                Severity::Warning,
+                "[Version] has no PnpLockdown directive".to_string(),
+                version.and_then(InfSection::span),
+            )],
+            Some((entry, span)) => {
+                let value = entry
+                    .value()
+                    .map(|value| value.fields().join(","))
+                    .unwrap_or_default();
+                if context.strings.expand(&value).as_deref() == Ok("1") {
+                    return vec![];
+                }
+                vec![report(
+                    self.code(),
+                    Severity::Warning,
+                    format!("PnpLockdown is {value}, expected 1"),
+                    span,
+                )]
+            }
+        }
+    }
+}
+
+struct MissingCatalogFile;
+
+impl LintRule for MissingCatalogFile {
+    fn code(&self) -> &'static str {
+        "missing-catalog-file"
+    }
+
+    fn description(&self) -> &'static str {
+        "[Version] names the catalog file that signs the driver package"
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<Diagnostic> {
+        let version = context.inf_file.section("Version");
+        let has_catalog = version.is_some_and(|version| {
+            version.entries().iter().any(|entry| {
+                entry.key().is_some_and(|key| {
+                    let name = key.split_once('.').map_or(key, |(name, _)| name);
+                    name.eq_ignore_ascii_case("CatalogFile")
+                })
+            })
+        });
+        if has_catalog {
+            return vec![];
+        }
+        vec![report(
+            self.code(),
+            Severity::Warning,
+            "[Version] has no CatalogFile directive".to_string(),
+            version.and_then(InfSection::span),
+        )]
+    }
+}
+
+struct UndefinedString;
+
+impl LintRule for UndefinedString {
+    fn code(&self) -> &'static str {
+        "undefined-string"
+    }
+
+    fn description(&self) -> &'static str {
+        "every %strkey% token is defined in a [Strings] section"
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<Diagnostic> {
+        // a token defined for some locales only still expands on those systems
+        let mut strings = StringTable::default();
+        let is_strings =
+            |section: &InfSection| base_name(section.name()).eq_ignore_ascii_case("Strings");
+        for section in context.inf_file.sections.values().filter(|s| is_strings(s)) {
+            strings.extend_from_section(section);
+        }
+
+        let mut diagnostics = vec![];
+        for section in context.inf_file.sections.values() {
+            if is_strings(section) {
+                continue;
+            }
+            for (entry, span) in section.entries_with_spans() {
+                let value = entry
+                    .value()
+                    .map(|value| value.fields())
+                    .unwrap_or_default();
+                let mut undefined: Vec<String> = vec![];
+                for text in entry.key().into_iter().chain(value) {
+                    for token in strings.undefined_tokens(text) {
+                        if !undefined.iter().any(|t| t.eq_ignore_ascii_case(&token)) {
+                            undefined.push(token);
+                        }
+                    }
+                }
+                diagnostics.extend(undefined.into_iter().map(|token| {
+                    report(
+                        self.code(),
+                        Severity::Error,
+                        format!("%{token}% in [{}] is not defined", section.name()),
+                        span,
+                    )
+                }));
+            }
+        }
+        diagnostics
+    }
+}
+
+struct UnreferencedSection;
+
+impl LintRule for UnreferencedSection {
+    fn code(&self) -> &'static str {
+        "unreferenced-section"
+    }
+
+    fn description(&self) -> &'static str {
+        "every section is read by Windows or referenced from a section that is"
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<Diagnostic> {
+        let inf_file = context.inf_file;
+        inf_file
+            .section_graph()
+            .unreachable_sections()
+            .into_iter()
+            .filter_map(|name| inf_file.section(name))
+            .map(|section| {
+                report(
+                    self.code(),
+                    Severity::Warning,
+                    format!("[{}] is not referenced", section.name()),
+                    section.span(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Reports the [`crate::ComplianceViolation`]s with one code that apply at the level
+struct ComplianceRule {
+    code: &'static str,
+    description: &'static str,
+}
+
+impl LintRule for ComplianceRule {
+    fn code(&self) -> &'static str {
+        self.code
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<Diagnostic> {
+        if context.level == ComplianceLevel::Legacy {
+            return vec![];
+        }
+        context
+            .violations
+            .iter()
+            .filter(|violation| violation.code == self.code && violation.level <= context.level)
+            .map(|violation| {
+                report(
+                    self.code,
+                    Severity::Error,
+                    violation.message.clone(),
+                    violation.span,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(inf_file: &WinInfFile, linter: &Linter) -> Vec<&'static str> {
+        linter.run(inf_file).iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn test_reachable_sections() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\n",
+                "PnpLockdown = 1\n",
+                "CatalogFile = a.cat\n",
+                "[Manufacturer]\n",
+                "%Mfg% = Models, NTamd64\n",
+                "[Models.NTamd64]\n",
+                "%Desc% = Inst, PCI\\VEN_1234\n",
+                "[Inst.NT]\n",
+                "CopyFiles = Files, @a.sys\n",
+                "[Inst.NT.Services]\n",
+                "AddService = svc, 2, Svc_Inst, Svc_Log\n",
+                "[Svc_Inst]\n",
+                "AddReg = %RegSection%\n",
+                "[Svc_Reg]\n",
+                "[Files]\n",
+                "[Orphan]\n",
+                "[Strings]\n",
+                "Mfg = Contoso\n",
+                "Desc = Device\n",
+                "RegSection = Svc_Reg\n",
+            ))
+            .unwrap();
+        let diagnostics = Linter::default().run(&inf_file);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["[Orphan] is not referenced"]);
+        assert_eq!(diagnostics[0].span.unwrap().start.line, 16);
+    }
+
+    #[test]
+    fn test_levels_and_disabled_rules() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\n",
+                "PnpLockdown = 0\n",
+                "[DefaultInstall]\n",
+                "CopyFiles = Files\n",
+                "[DestinationDirs]\n",
+                "Files = 11\n",
+                "DefaultDestDir = 16422\n",
+                "[Files]\n",
+                "%Missing%.dll\n",
+            ))
+            .unwrap();
+
+        let mut linter = Linter::new(ComplianceLevel::Legacy);
+        assert_eq!(
+            codes(&inf_file, &linter),
+            [
+                "missing-catalog-file",
+                "missing-pnp-lockdown",
+                "undefined-string"
+            ]
+        );
+
+        let mut linter_dch = Linter::new(ComplianceLevel::Declarative);
+        linter_dch.disable("missing-catalog-file");
+        assert!(!linter_dch.is_enabled("missing-catalog-file"));
+        assert_eq!(
+            codes(&inf_file, &linter_dch),
+            [
+                "missing-pnp-lockdown",
+                "copyfiles-system32",
+                "disallowed-dirid",
+                "undefined-string"
+            ]
+        );
+
+        linter.disable("undefined-string");
+        linter.disable("missing-pnp-lockdown");
+        linter.enable("missing-pnp-lockdown");
+        assert_eq!(
+            codes(&inf_file, &linter),
+            ["missing-catalog-file", "missing-pnp-lockdown"]
+        );
+        let rules: Vec<_> = linter.rules().map(|rule| rule.code()).collect();
+        assert!(rules.contains(&"unreferenced-section"));
+        assert!(rules.contains(&"copyfiles-system32"));
+    }
+
+    #[test]
+    fn test_missing_version_section() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str("[DefaultInstall]\nAddReg = Reg\n[Reg]\nHKR,,A,,1\n")
+            .unwrap();
+        let diagnostics = inf_file.lint(ComplianceLevel::Legacy);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.code, d.message.as_str(), d.span))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "missing-pnp-lockdown",
+                    "[Version] has no PnpLockdown directive",
+                    None
+                ),
+                (
+                    "missing-catalog-file",
+                    "[Version] has no CatalogFile directive",
+                    None
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,496 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::fields::parse_number;
+use crate::{InfEntry, InfSection, StringsError, WinInfFile};
+
+/// Errors that can occur while reading the `[Manufacturer]` section
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ManufacturerError {
+    /// The INF file has no `[Manufacturer]` section
+    #[error("Missing [Manufacturer] section")]
+    MissingSection,
+    /// A manufacturer entry does not name a models section
+    #[error("Invalid manufacturer entry: {0}")]
+    InvalidEntry(String),
+    /// A models section entry does not name an install section
+    #[error("Invalid models entry: {0}")]
+    InvalidModelEntry(String),
+    /// A TargetOSVersion decoration could not be parsed
+    #[error("Invalid TargetOSVersion decoration: {0}")]
+    InvalidTargetOsVersion(String),
+    /// A `%strkey%` token in a manufacturer name could not be expanded
+    #[error("Failed to expand string token: {0}")]
+    Strings(#[from] StringsError),
+}
+
+/// A processor architecture used in INF decorations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arch {
+    /// 32-bit x86, `x86`
+    X86,
+    /// 64-bit x86, `amd64`
+    Amd64,
+    /// 32-bit ARM, `arm`
+    Arm,
+    /// 64-bit ARM, `arm64`
+    Arm64,
+    /// Itanium, `ia64`
+    Ia64,
+}
+
+impl Arch {
+    /// Returns the name used for the architecture in INF decorations, e.g. `amd64`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Arch::X86 => "x86",
+            Arch::Amd64 => "amd64",
+            Arch::Arm => "arm",
+            Arch::Arm64 => "arm64",
+            Arch::Ia64 => "ia64",
+        }
+    }
+}
+
+impl FromStr for Arch {
+    type Err = ManufacturerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Arch::X86, Arch::Amd64, Arch::Arm, Arch::Arm64, Arch::Ia64]
+            .into_iter()
+            .find(|arch| arch.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ManufacturerError::InvalidTargetOsVersion(s.to_string()))
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The Windows installation a driver is installed on
+///
+/// Used to pick among TargetOSVersion decorated sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Platform {
+    /// The processor architecture
+    pub arch: Arch,
+    /// The OS major version, e.g. `10`
+    pub major_version: u32,
+    /// The OS minor version, e.g. `0`
+    pub minor_version: u32,
+    /// The product type: `1` workstation, `2` domain controller, `3` server
+    pub product_type: u32,
+    /// The `VER_SUITE_*` flags of the installation
+    pub suite_mask: u32,
+    /// The OS build number, e.g. `19041`
+    pub build_number: u32,
+}
+
+impl Platform {
+    /// Creates a workstation platform without suite flags
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Arch, Platform};
+    ///
+    /// let platform = Platform {
+    ///     build_number: 22621,
+    ///     ..Platform::new(Arch::Amd64, 10, 0)
+    /// };
+    /// assert_eq!(platform.product_type, 1);
+    /// ```
+    pub fn new(arch: Arch, major_version: u32, minor_version: u32) -> Self {
+        Platform {
+            arch,
+            major_version,
+            minor_version,
+            product_type: 1,
+            suite_mask: 0,
+            build_number: 0,
+        }
+    }
+}
+
+/// A parsed TargetOSVersion decoration such as `NTamd64.10.0...19041`
+///
+/// The `$ARCH$` placeholder that `stampinf` replaces at build time is treated as
+/// matching any architecture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetOsVersion {
+    /// The decoration as written in the INF file
+    pub decoration: String,
+    /// The architecture, `None` if the decoration applies to all architectures
+    pub arch: Option<Arch>,
+    /// The minimum OS major version
+    pub major_version: Option<u32>,
+    /// The minimum OS minor version
+    pub minor_version: Option<u32>,
+    /// The required product type
+    pub product_type: Option<u32>,
+    /// The `VER_SUITE_*` flags that must all be present
+    pub suite_mask: Option<u32>,
+    /// The minimum OS build number
+    pub build_number: Option<u32>,
+}
+
+impl FromStr for TargetOsVersion {
+    type Err = ManufacturerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ManufacturerError::InvalidTargetOsVersion(s.to_string());
+        let decoration = s.trim();
+        if !decoration
+            .get(..2)
+            .is_some_and(|nt| nt.eq_ignore_ascii_case("NT"))
+        {
+            return Err(invalid());
+        }
+
+        let mut parts = decoration[2..].split('.');
+        let arch = match parts.next().unwrap_or_default() {
+            "" | "$ARCH$" => None,
+            arch => Some(arch.parse().map_err(|_| invalid())?),
+        };
+        let mut numbers = [None; 5];
+        for slot in numbers.iter_mut() {
+            match parts.next() {
+                None => break,
+                Some("") => {}
+                Some(part) => *slot = Some(parse_number(part).ok_or_else(invalid)?),
+            }
+        }
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        let [
+            major_version,
+            minor_version,
+            product_type,
+            suite_mask,
+            build_number,
+        ] = numbers;
+        Ok(TargetOsVersion {
+            decoration: decoration.to_string(),
+            arch,
+            major_version,
+            minor_version,
+            product_type,
+            suite_mask,
+            build_number,
+        })
+    }
+}
+
+impl TargetOsVersion {
+    /// Returns `true` if the decoration names an architecture, or the `$ARCH$`
+    /// placeholder that becomes one at build time
+    fn names_arch(&self) -> bool {
+        self.arch.is_some()
+            || self
+                .decoration
+                .get(2..)
+                .is_some_and(|rest| rest.starts_with("$ARCH$"))
+    }
+
+    /// Returns `true` if the decoration applies to the given platform
+    pub fn matches(&self, platform: &Platform) -> bool {
+        let version = (
+            self.major_version.unwrap_or(0),
+            self.minor_version.unwrap_or(0),
+        );
+        self.arch.is_none_or(|arch| arch == platform.arch)
+            && version <= (platform.major_version, platform.minor_version)
+            && self
+                .product_type
+                .is_none_or(|product_type| product_type == platform.product_type)
+            && self
+                .suite_mask
+                .is_none_or(|mask| platform.suite_mask & mask == mask)
+            && self
+                .build_number
+                .is_none_or(|build| build <= platform.build_number)
+    }
+
+    /// Key used to rank matching decorations, the highest key is the best match
+    ///
+    /// Setup prefers the decoration for the most recent OS version, and among equal
+    /// versions the most specific one.
+    fn rank(&self) -> (u32, u32, u32, bool, bool, bool) {
+        (
+            self.major_version.unwrap_or(0),
+            self.minor_version.unwrap_or(0),
+            self.build_number.unwrap_or(0),
+            self.arch.is_some(),
+            self.product_type.is_some(),
+            self.suite_mask.is_some(),
+        )
+    }
+}
+
+impl fmt::Display for TargetOsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.decoration)
+    }
+}
+
+/// An entry of the `[Manufacturer]` section
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manufacturer {
+    /// The manufacturer name, with `%strkey%` tokens expanded
+    pub name: String,
+    /// The name of the models section, without decoration
+    pub models_section: String,
+    /// The TargetOSVersion decorations listed for the models section
+    pub target_os_versions: Vec<TargetOsVersion>,
+}
+
+impl Manufacturer {
+    /// Returns the decoration Setup would pick for the platform
+    ///
+    /// Returns `None` if no decoration applies. Except on x86, Setup only uses
+    /// decorations that name the architecture, such as `NTamd64`.
+    pub fn best_target_os_version(&self, platform: &Platform) -> Option<&TargetOsVersion> {
+        self.target_os_versions
+            .iter()
+            .filter(|t| t.matches(platform))
+            .filter(|t| platform.arch == Arch::X86 || t.names_arch())
+            .max_by_key(|t| t.rank())
+    }
+
+    /// Returns the name of the models section to use on the platform
+    ///
+    /// Without a matching decoration, Setup falls back to the undecorated models section
+    /// on x86 and finds no models section on other architectures.
+    pub fn models_section_name(&self, platform: &Platform) -> Option<String> {
+        match self.best_target_os_version(platform) {
+            Some(target) => Some(format!("{}.{}", self.models_section, target.decoration)),
+            None if platform.arch == Arch::X86 => Some(self.models_section.clone()),
+            None => None,
+        }
+    }
+}
+
+/// A models section selected for a platform
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelsSection<'a> {
+    /// The manufacturer entry that references the section
+    pub manufacturer: Manufacturer,
+    /// The decoration the section was selected by, `None` for the undecorated section
+    pub target_os_version: Option<TargetOsVersion>,
+    /// The models section
+    pub section: &'a InfSection,
+}
+
+impl WinInfFile {
+    /// Returns the entries of the `[Manufacturer]` section
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Arch, WinInfFile};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/sampledisplay.inf")).unwrap();
+    /// let manufacturers = inf_file.manufacturers().unwrap();
+    /// assert_eq!(manufacturers[0].models_section, "Standard");
+    /// assert_eq!(manufacturers[0].target_os_versions[2].arch, Some(Arch::Arm64));
+    /// ```
+    pub fn manufacturers(&self) -> Result<Vec<Manufacturer>, ManufacturerError> {
+        let section = self
+            .section("Manufacturer")
+            .ok_or(ManufacturerError::MissingSection)?;
+        let strings = self.string_table();
+
+        let mut manufacturers = vec![];
+        for entry in section.entries() {
+            let (name, models_section, decorations) = match entry {
+                InfEntry::KeyValue(key, Some(value)) => {
+                    let fields = value.fields();
+                    let (models_section, decorations) = fields.split_first().unwrap_or((&"", &[]));
+                    (key.as_str(), *models_section, decorations.to_vec())
+                }
+                InfEntry::OnlyValue(value) => {
+                    // `%strkey%` alone names a models section after the token
+                    let name = value.field(0).unwrap_or_default();
+                    (name, name.trim_matches('%'), vec![])
+                }
+                InfEntry::KeyValue(key, None) => (key.as_str(), "", vec![]),
+            };
+            if models_section.is_empty() {
+                return Err(ManufacturerError::InvalidEntry(name.to_string()));
+            }
+
+            manufacturers.push(Manufacturer {
+                name: strings.expand(name)?,
+                models_section: models_section.to_string(),
+                target_os_versions: decorations
+                    .iter()
+                    .filter(|d| !d.is_empty())
+                    .map(|d| d.parse())
+                    .collect::<Result<_, _>>()?,
+            });
+        }
+        Ok(manufacturers)
+    }
+
+    /// Returns the models sections Setup would use on the platform
+    ///
+    /// For every manufacturer the best matching decorated section is selected, or on x86
+    /// the undecorated section when no decoration applies. Manufacturers without a
+    /// selected section, or whose selected section does not exist in the file, are
+    /// skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Arch, Platform, WinInfFile};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/sampledisplay.inf")).unwrap();
+    /// let models = inf_file.models_sections(&Platform::new(Arch::Arm64, 10, 0)).unwrap();
+    /// assert_eq!(models[0].section.name(), "Standard.NTarm64");
+    /// ```
+    pub fn models_sections(
+        &self,
+        platform: &Platform,
+    ) -> Result<Vec<ModelsSection<'_>>, ManufacturerError> {
+        let mut models_sections = vec![];
+        for manufacturer in self.manufacturers()? {
+            let target_os_version = manufacturer.best_target_os_version(platform).cloned();
+            let section = manufacturer
+                .models_section_name(platform)
+                .and_then(|name| self.section(&name));
+            if let Some(section) = section {
+                models_sections.push(ModelsSection {
+                    manufacturer,
+                    target_os_version,
+                    section,
+                });
+            }
+        }
+        Ok(models_sections)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_os_version_parse() {
+        let target: TargetOsVersion = "NTamd64.10.0...19041".parse().unwrap();
+        assert_eq!(target.arch, Some(Arch::Amd64));
+        assert_eq!(target.major_version, Some(10));
+        assert_eq!(target.minor_version, Some(0));
+        assert_eq!(target.product_type, None);
+        assert_eq!(target.suite_mask, None);
+        assert_eq!(target.build_number, Some(19041));
+
+        let target: TargetOsVersion = "NT$ARCH$.6.1.0x3.0x00000010".parse().unwrap();
+        assert_eq!(target.arch, None);
+        assert_eq!(target.product_type, Some(3));
+        assert_eq!(target.suite_mask, Some(0x10));
+
+        let target: TargetOsVersion = "nt".parse().unwrap();
+        assert_eq!(target.arch, None);
+        assert_eq!(target.major_version, None);
+
+        assert!("amd64".parse::<TargetOsVersion>().is_err());
+        assert!("NTmips".parse::<TargetOsVersion>().is_err());
+        assert!("NTamd64.ten".parse::<TargetOsVersion>().is_err());
+        assert!("NTamd64.10.0.1.2.3.4".parse::<TargetOsVersion>().is_err());
+        assert!("€x".parse::<TargetOsVersion>().is_err());
+    }
+
+    #[test]
+    fn test_target_os_version_matches() {
+        let win10 = Platform {
+            build_number: 19045,
+            ..Platform::new(Arch::Amd64, 10, 0)
+        };
+        let matches = |decoration: &str| {
+            decoration
+                .parse::<TargetOsVersion>()
+                .unwrap()
+                .matches(&win10)
+        };
+        assert!(matches("NTamd64"));
+        assert!(matches("NT$ARCH$.10.0...19041"));
+        assert!(matches("NTamd64.6.1"));
+        assert!(matches("NT.10.0.1"));
+        assert!(!matches("NTarm64"));
+        assert!(!matches("NTamd64.10.0...22000"));
+        assert!(!matches("NTamd64.10.1"));
+        assert!(!matches("NTamd64.10.0.3"));
+        assert!(!matches("NTamd64.10.0..0x10"));
+    }
+
+    #[test]
+    fn test_best_target_os_version() {
+        let manufacturer = Manufacturer {
+            name: "Contoso".to_string(),
+            models_section: "Models".to_string(),
+            target_os_versions: ["NT", "NTamd64", "NTamd64.6.1", "NTamd64.10.0...22000"]
+                .iter()
+                .map(|d| d.parse().unwrap())
+                .collect(),
+        };
+        let platform = |arch, build_number| Platform {
+            build_number,
+            ..Platform::new(arch, 10, 0)
+        };
+        assert_eq!(
+            manufacturer
+                .models_section_name(&platform(Arch::Amd64, 19041))
+                .as_deref(),
+            Some("Models.NTamd64.6.1")
+        );
+        assert_eq!(
+            manufacturer
+                .models_section_name(&platform(Arch::Amd64, 22621))
+                .as_deref(),
+            Some("Models.NTamd64.10.0...22000")
+        );
+        assert_eq!(
+            manufacturer.models_section_name(&platform(Arch::Arm64, 22621)),
+            None
+        );
+        assert_eq!(
+            manufacturer
+                .models_section_name(&platform(Arch::X86, 22621))
+                .as_deref(),
+            Some("Models.NT")
+        );
+        assert_eq!(
+            manufacturer
+                .models_section_name(&Platform::new(Arch::Amd64, 5, 1))
+                .as_deref(),
+            Some("Models.NTamd64")
+        );
+
+        let placeholder = Manufacturer {
+            target_os_versions: vec!["NT$ARCH$.10.0...19041".parse().unwrap()],
+            ..manufacturer
+        };
+        assert_eq!(
+            placeholder
+                .models_section_name(&platform(Arch::Arm64, 22621))
+                .as_deref(),
+            Some("Models.NT$ARCH$.10.0...19041")
+        );
+        let undecorated = Manufacturer {
+            target_os_versions: vec![],
+            ..placeholder
+        };
+        assert_eq!(
+            undecorated.models_section_name(&platform(Arch::Amd64, 22621)),
+            None
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use crate::version::{catalog_files, select_catalog_file};
+use crate::{
+    Arch, FilesError, InfSection, ManufacturerError, Platform, SourceMediaError, VersionError,
+    WinInfFile,
+};
+
+/// Errors that can occur while checking a driver package directory
+#[derive(Debug, thiserror::Error)]
+pub enum PackageError {
+    /// The package directory could not be read
+    #[error("Failed to read package directory: {0}")]
+    Io(#[from] io::Error),
+    /// The source media sections could not be read
+    #[error("Invalid source media: {0}")]
+    SourceMedia(#[from] SourceMediaError),
+    /// A `CopyFiles` directive could not be expanded
+    #[error("Invalid CopyFiles directive: {0}")]
+    Files(#[from] FilesError),
+    /// A `CatalogFile` directive could not be read
+    #[error("Invalid CatalogFile directive: {0}")]
+    Version(#[from] VersionError),
+    /// The models sections could not be read
+    #[error("Invalid models section: {0}")]
+    Manufacturer(#[from] ManufacturerError),
+}
+
+/// The result of checking a driver package directory against its INF file
+///
+/// Paths are relative to the package directory and use `\` as separator. Each list is
+/// sorted, ignoring case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageReport {
+    /// Files referenced by `[SourceDisksFiles]` or `CopyFiles` that are not in the directory
+    pub missing_files: Vec<String>,
+    /// Files copied by `CopyFiles` that `[SourceDisksFiles]` does not list
+    pub unlisted_files: Vec<String>,
+    /// Files in the directory that the INF file does not reference
+    ///
+    /// INF files at the top of the directory are not reported.
+    pub unreferenced_files: Vec<String>,
+    /// Catalog files the package needs that are not in the directory
+    ///
+    /// With an architecture this is the catalog Setup uses for it, otherwise every
+    /// catalog named by a `CatalogFile` directive.
+    pub missing_catalogs: Vec<String>,
+}
+
+impl PackageReport {
+    /// Returns `true` if no problem was found
+    pub fn is_complete(&self) -> bool {
+        self.missing_files.is_empty()
+            && self.unlisted_files.is_empty()
+            && self.unreferenced_files.is_empty()
+            && self.missing_catalogs.is_empty()
+    }
+}
+
+/// Collects the files below `dir`, keyed by their lowercase relative path
+fn package_files(dir: &Path, prefix: &str, files: &mut BTreeMap<String, String>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}\\{name}")
+        };
+        if entry.file_type()?.is_dir() {
+            package_files(&entry.path(), &path, files)?;
+        } else {
+            files.insert(path.to_ascii_lowercase(), path);
+        }
+    }
+    Ok(())
+}
+
+impl WinInfFile {
+    /// Returns the sections whose `CopyFiles` directives install the package on `arch`
+    ///
+    /// These are the DDInstall sections of the models entries Setup would use on the
+    /// newest release of Windows for the architecture, and `[DefaultInstall]`, each
+    /// selected for the architecture and with its companion sections. Without an
+    /// architecture every section is returned.
+    fn package_install_sections(
+        &self,
+        arch: Option<Arch>,
+    ) -> Result<Vec<&InfSection>, ManufacturerError> {
+        let Some(arch) = arch else {
+            return Ok(self.sections.values().collect());
+        };
+        let platform = Platform {
+            build_number: u32::MAX,
+            ..Platform::new(arch, u32::MAX, u32::MAX)
+        };
+        // primitive drivers only have a [DefaultInstall] section
+        let mut names = vec!["DefaultInstall".to_string()];
+        if self.section("Manufacturer").is_some() {
+            for (_, model) in self.model_entries(&platform)? {
+                names.push(model.install_section);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut sections = vec![];
+        for name in names {
+            let Some(install) = self.ddinstall(&name, &platform) else {
+                continue;
+            };
+            for section in [install.section]
+                .into_iter()
+                .chain(install.companions().map(|(_, section)| section))
+            {
+                if seen.insert(section.name().to_ascii_lowercase()) {
+                    sections.push(section);
+                }
+            }
+        }
+        Ok(sections)
+    }
+
+    /// Checks that a driver package directory holds the files the INF file needs
+    ///
+    /// Referenced files are the payload files of [`WinInfFile::source_manifest`] for the
+    /// architecture, the files copied by the install sections Setup uses on the
+    /// architecture and the catalog it uses, `CatalogFile.NT<arch>` or else the
+    /// undecorated `CatalogFile`. The install sections are the DDInstall sections of the
+    /// selected models sections and `[DefaultInstall]`, with their companion sections.
+    /// Without an architecture the files copied by every `CopyFiles` directive and every
+    /// catalog are required. Catalogs of other architectures are not required but still
+    /// count as referenced. Names are compared ignoring case, as Windows does.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use inf_rs::{Arch, WinInfFile};
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("package/driver.inf")).unwrap();
+    /// let report = inf_file.check_package(Path::new("package"), Some(Arch::Amd64)).unwrap();
+    /// for file in &report.missing_files {
+    ///     println!("missing: {file}");
+    /// }
+    /// ```
+    pub fn check_package(
+        &self,
+        dir: &Path,
+        arch: Option<Arch>,
+    ) -> Result<PackageReport, PackageError> {
+        let mut present = BTreeMap::new();
+        package_files(dir, "", &mut present)?;
+
+        // lowercase path -> path as written in the INF file
+        let mut referenced = BTreeMap::new();
+        let mut report = PackageReport::default();
+
+        let manifest = self.source_manifest(arch)?;
+        for file in &manifest.files {
+            if let Some(path) = manifest.source_path(&file.name) {
+                referenced.insert(path.to_ascii_lowercase(), path);
+            }
+        }
+
+        for section in self.package_install_sections(arch)? {
+            if section.get("CopyFiles").is_none() {
+                continue;
+            }
+            for operation in self.copy_files(section.name())? {
+                if manifest.file(&operation.source_file).is_some() {
+                    continue;
+                }
+                let key = operation.source_file.to_ascii_lowercase();
+                if let Entry::Vacant(entry) = referenced.entry(key) {
+                    report.unlisted_files.push(operation.source_file.clone());
+                    entry.insert(operation.source_file);
+                }
+            }
+        }
+
+        for (key, path) in &referenced {
+            if !present.contains_key(key) {
+                report.missing_files.push(path.clone());
+            }
+        }
+
+        if let Some(version) = self.section("Version") {
+            let catalogs = catalog_files(version, &self.string_table())?;
+            let required: Vec<_> = match arch {
+                Some(arch) => select_catalog_file(&catalogs, Some(&format!("NT{arch}")))
+                    .into_iter()
+                    .collect(),
+                None => catalogs.iter().collect(),
+            };
+            for catalog in required {
+                if !present.contains_key(&catalog.file_name.to_ascii_lowercase())
+                    && !report.missing_catalogs.contains(&catalog.file_name)
+                {
+                    report.missing_catalogs.push(catalog.file_name.clone());
+                }
+            }
+            for catalog in catalogs {
+                referenced.insert(catalog.file_name.to_ascii_lowercase(), catalog.file_name);
+            }
+        }
+
+        for (key, path) in &present {
+            let top_level_inf = !key.contains('\\') && key.ends_with(".inf");
+            if !top_level_inf && !referenced.contains_key(key) {
+                report.unreferenced_files.push(path.clone());
+            }
+        }
+
+        report
+            .unlisted_files
+            .sort_by_key(|path| path.to_ascii_lowercase());
+        report
+            .missing_catalogs
+            .sort_by_key(|path| path.to_ascii_lowercase());
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn package_dir(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("inf-rs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for file in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, b"").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_check_package() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\n",
+                "CatalogFile = pkg.cat\n",
+                "CatalogFile.NTamd64 = pkg64.cat\n",
+                "[SourceDisksNames]\n",
+                "1 = Disk,,,amd64\n",
+                "[SourceDisksFiles]\n",
+                "driver.sys = 1\n",
+                "helper.dll = 1,bin\n",
+                "[DefaultInstall]\n",
+                "CopyFiles = Files, @extra.dll\n",
+                "[Files]\n",
+                "driver.sys\n",
+            ))
+            .unwrap();
+        let dir = package_dir(
+            "check",
+            &[
+                "pkg.inf",
+                "PKG.CAT",
+                "amd64/Driver.sys",
+                "notes.txt",
+                "amd64/old.sys",
+            ],
+        );
+        let report = inf_file.check_package(&dir, Some(Arch::Amd64)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            report.missing_files,
+            vec!["amd64\\bin\\helper.dll", "extra.dll"]
+        );
+        assert_eq!(report.unlisted_files, vec!["extra.dll"]);
+        assert_eq!(
+            report.unreferenced_files,
+            vec!["amd64\\old.sys", "notes.txt"]
+        );
+        assert_eq!(report.missing_catalogs, vec!["pkg64.cat"]);
+        assert!(!report.is_complete());
+    }
+
+    #[test]
+    fn test_check_package_catalogs() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\n",
+                "CatalogFile = pkg.cat\n",
+                "CatalogFile.NTamd64 = pkg64.cat\n",
+                "CatalogFile.NTarm64 = pkgarm64.cat\n",
+            ))
+            .unwrap();
+        let dir = package_dir("catalogs", &["pkg.inf", "pkg64.cat"]);
+        let missing = |arch| {
+            let report = inf_file.check_package(&dir, arch).unwrap();
+            assert!(report.unreferenced_files.is_empty());
+            report.missing_catalogs
+        };
+        assert!(missing(Some(Arch::Amd64)).is_empty());
+        assert_eq!(missing(Some(Arch::Arm64)), vec!["pkgarm64.cat"]);
+        assert_eq!(missing(Some(Arch::X86)), vec!["pkg.cat"]);
+        assert_eq!(missing(None), vec!["pkg.cat", "pkgarm64.cat"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_package_complete() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str("[SourceDisksNames]\n1 = Disk\n[SourceDisksFiles]\na.sys = 1\n")
+            .unwrap();
+        let dir = package_dir("complete", &["a.inf", "a.sys"]);
+        let report = inf_file.check_package(&dir, None).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(report.is_complete());
+
+        assert!(matches!(
+            inf_file.check_package(&dir, None),
+            Err(PackageError::Io(_))
+        ));
+    }
+}
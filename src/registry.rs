@@ -0,0 +1,524 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::fields::parse_number;
+use crate::{InfEntry, InfSection, StringTable, StringsError, WinInfFile};
+
+/// Errors that can occur while reading registry sections
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RegistryError {
+    /// The registry section referenced by a directive does not exist
+    #[error("Missing registry section: {0}")]
+    MissingSection(String),
+    /// A line does not have the fields its directive requires
+    #[error("Malformed registry line: {0}")]
+    MalformedLine(String),
+    /// The registry root is not one of `HKCR`, `HKCU`, `HKLM`, `HKU` or `HKR`
+    #[error("Invalid registry root: {0}")]
+    InvalidRoot(String),
+    /// The flags field is not a number
+    #[error("Invalid registry flags: {0}")]
+    InvalidFlags(String),
+    /// The value does not match the type given by the flags
+    #[error("Invalid registry value: {0}")]
+    InvalidValue(String),
+    /// A `%strkey%` token in the line could not be expanded
+    #[error("Failed to expand string token: {0}")]
+    Strings(#[from] StringsError),
+}
+
+/// The root key of a registry line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegistryRoot {
+    /// `HKCR`, `HKEY_CLASSES_ROOT`
+    ClassesRoot,
+    /// `HKCU`, `HKEY_CURRENT_USER`
+    CurrentUser,
+    /// `HKLM`, `HKEY_LOCAL_MACHINE`
+    LocalMachine,
+    /// `HKU`, `HKEY_USERS`
+    Users,
+    /// `HKR`, the key relative to the section that references the registry section
+    Relative,
+}
+
+impl RegistryRoot {
+    /// Returns the abbreviation used in INF files, e.g. `HKLM`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RegistryRoot::ClassesRoot => "HKCR",
+            RegistryRoot::CurrentUser => "HKCU",
+            RegistryRoot::LocalMachine => "HKLM",
+            RegistryRoot::Users => "HKU",
+            RegistryRoot::Relative => "HKR",
+        }
+    }
+}
+
+impl FromStr for RegistryRoot {
+    type Err = RegistryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            RegistryRoot::ClassesRoot,
+            RegistryRoot::CurrentUser,
+            RegistryRoot::LocalMachine,
+            RegistryRoot::Users,
+            RegistryRoot::Relative,
+        ]
+        .into_iter()
+        .find(|root| root.as_str().eq_ignore_ascii_case(s.trim()))
+        .ok_or_else(|| RegistryError::InvalidRoot(s.to_string()))
+    }
+}
+
+impl fmt::Display for RegistryRoot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The flags field of an `AddReg` line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AddRegFlags(pub u32);
+
+impl AddRegFlags {
+    /// `FLG_ADDREG_BINVALUETYPE`, the value is binary data
+    pub const BINVALUETYPE: u32 = 0x0000_0001;
+    /// `FLG_ADDREG_NOCLOBBER`, do not replace an existing value
+    pub const NOCLOBBER: u32 = 0x0000_0002;
+    /// `FLG_ADDREG_DELVAL`, delete the value
+    pub const DELVAL: u32 = 0x0000_0004;
+    /// `FLG_ADDREG_APPEND`, append to an existing `REG_MULTI_SZ` value
+    pub const APPEND: u32 = 0x0000_0008;
+    /// `FLG_ADDREG_KEYONLY`, create the key but no value
+    pub const KEYONLY: u32 = 0x0000_0010;
+    /// `FLG_ADDREG_OVERWRITEONLY`, only replace an existing value
+    pub const OVERWRITEONLY: u32 = 0x0000_0020;
+    /// `FLG_ADDREG_64BITKEY`, use the 64-bit registry view
+    pub const KEY64: u32 = 0x0000_1000;
+    /// `FLG_ADDREG_KEYONLY_COMMON`, create the key, ignoring the value type
+    pub const KEYONLY_COMMON: u32 = 0x0000_2000;
+    /// `FLG_ADDREG_32BITKEY`, use the 32-bit registry view
+    pub const KEY32: u32 = 0x0000_4000;
+    /// `FLG_ADDREG_TYPE_SZ`
+    pub const TYPE_SZ: u32 = 0x0000_0000;
+    /// `FLG_ADDREG_TYPE_MULTI_SZ`
+    pub const TYPE_MULTI_SZ: u32 = 0x0001_0000;
+    /// `FLG_ADDREG_TYPE_EXPAND_SZ`
+    pub const TYPE_EXPAND_SZ: u32 = 0x0002_0000;
+    /// `FLG_ADDREG_TYPE_BINARY`
+    pub const TYPE_BINARY: u32 = 0x0000_0001;
+    /// `FLG_ADDREG_TYPE_DWORD`
+    pub const TYPE_DWORD: u32 = 0x0001_0001;
+    /// `FLG_ADDREG_TYPE_NONE`
+    pub const TYPE_NONE: u32 = 0x0002_0001;
+
+    /// The bits of the flags that select the value type
+    const TYPE_MASK: u32 = 0xffff_0001;
+
+    /// Returns `true` if all bits of `flag` are set
+    pub fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// Returns the type bits of the flags, e.g. [`AddRegFlags::TYPE_DWORD`]
+    pub fn value_type(&self) -> u32 {
+        self.0 & Self::TYPE_MASK
+    }
+}
+
+/// The typed value of an `AddReg` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryValue {
+    /// `REG_SZ`
+    String(String),
+    /// `REG_EXPAND_SZ`
+    ExpandString(String),
+    /// `REG_MULTI_SZ`, one string per field
+    MultiString(Vec<String>),
+    /// `REG_DWORD`
+    Dword(u32),
+    /// `REG_NONE`, with optional data
+    None(Vec<u8>),
+    /// Binary data of a registry type, `3` (`REG_BINARY`) unless the flags say otherwise
+    Binary {
+        /// The `REG_*` type of the value
+        value_type: u32,
+        /// The bytes of the value
+        data: Vec<u8>,
+    },
+}
+
+/// A line of a section referenced by an `AddReg` directive
+///
+/// `reg-root, [subkey], [value-entry-name], [flags], [value]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryEntry {
+    /// The root key
+    pub root: RegistryRoot,
+    /// The subkey below the root, empty for the root itself
+    pub subkey: String,
+    /// The value name, `None` for the default value
+    pub value_name: Option<String>,
+    /// The `FLG_ADDREG_*` flags
+    pub flags: AddRegFlags,
+    /// The value, `None` if the line only creates the key
+    pub value: Option<RegistryValue>,
+}
+
+/// A line of a section referenced by a `DelReg` directive
+///
+/// `reg-root, subkey, [value-entry-name], [flags], [value]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelRegEntry {
+    /// The root key
+    pub root: RegistryRoot,
+    /// The subkey below the root
+    pub subkey: String,
+    /// The value to delete, `None` to delete the whole subkey
+    pub value_name: Option<String>,
+    /// The `FLG_DELREG_*` flags
+    pub flags: u32,
+    /// The string to remove for `FLG_DELREG_MULTI_SZ_DELSTRING`
+    pub value: Option<String>,
+}
+
+/// A line of a section referenced by a `BitReg` directive
+///
+/// `reg-root, subkey, value-entry-name, [flags], byte-mask, byte-to-modify`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitRegEntry {
+    /// The root key
+    pub root: RegistryRoot,
+    /// The subkey below the root
+    pub subkey: String,
+    /// The binary value to modify
+    pub value_name: String,
+    /// The `FLG_BITREG_*` flags, bit `0x1` sets the masked bits, otherwise they are cleared
+    pub flags: u32,
+    /// The bits to set or clear
+    pub byte_mask: u8,
+    /// The zero-based index of the byte to modify
+    pub byte_to_modify: u32,
+}
+
+/// The fields of a registry line, with `%strkey%` tokens expanded
+fn expanded_fields(entry: &InfEntry, strings: &StringTable) -> Result<Vec<String>, RegistryError> {
+    let InfEntry::OnlyValue(value) = entry else {
+        return Err(RegistryError::MalformedLine(format!("{entry:?}")));
+    };
+    Ok(value
+        .fields()
+        .into_iter()
+        .map(|field| strings.expand(field))
+        .collect::<Result<_, _>>()?)
+}
+
+fn optional(field: Option<&String>) -> Option<String> {
+    field.filter(|f| !f.is_empty()).cloned()
+}
+
+fn parse_flags(field: Option<&String>) -> Result<u32, RegistryError> {
+    match field.map(|f| f.trim()) {
+        None | Some("") => Ok(0),
+        Some(flags) => {
+            parse_number(flags).ok_or_else(|| RegistryError::InvalidFlags(flags.to_string()))
+        }
+    }
+}
+
+fn parse_bytes(fields: &[String]) -> Result<Vec<u8>, RegistryError> {
+    fields
+        .iter()
+        .map(|byte| {
+            let hex = byte.trim();
+            let hex = hex.strip_prefix("0x").unwrap_or(hex);
+            u8::from_str_radix(hex, 16).map_err(|_| RegistryError::InvalidValue(byte.clone()))
+        })
+        .collect()
+}
+
+fn parse_value(
+    flags: AddRegFlags,
+    fields: &[String],
+) -> Result<Option<RegistryValue>, RegistryError> {
+    if fields.is_empty() || flags.contains(AddRegFlags::KEYONLY) {
+        return Ok(None);
+    }
+    let invalid = || RegistryError::InvalidValue(fields.join(","));
+    let first = fields[0].clone();
+    Ok(Some(match flags.value_type() {
+        AddRegFlags::TYPE_SZ => RegistryValue::String(first),
+        AddRegFlags::TYPE_EXPAND_SZ => RegistryValue::ExpandString(first),
+        AddRegFlags::TYPE_MULTI_SZ => RegistryValue::MultiString(fields.to_vec()),
+        AddRegFlags::TYPE_DWORD => match fields {
+            [number] => RegistryValue::Dword(parse_number(number).ok_or_else(invalid)?),
+            // legacy INFs spell out the four little-endian bytes
+            [_, _, _, _] => {
+                let bytes = parse_bytes(fields)?;
+                RegistryValue::Dword(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            _ => return Err(invalid()),
+        },
+        AddRegFlags::TYPE_NONE => RegistryValue::None(parse_bytes(fields)?),
+        value_type if value_type & AddRegFlags::BINVALUETYPE != 0 => RegistryValue::Binary {
+            value_type: match value_type >> 16 {
+                0 => 3,
+                value_type => value_type,
+            },
+            data: parse_bytes(fields)?,
+        },
+        _ => return Err(RegistryError::InvalidFlags(format!("{:#010x}", flags.0))),
+    }))
+}
+
+impl RegistryEntry {
+    /// Parses the lines of a section referenced by an `AddReg` directive
+    pub fn parse_section(
+        section: &InfSection,
+        strings: &StringTable,
+    ) -> Result<Vec<Self>, RegistryError> {
+        section
+            .entries()
+            .iter()
+            .map(|entry| {
+                let fields = expanded_fields(entry, strings)?;
+                let root = fields
+                    .first()
+                    .ok_or_else(|| RegistryError::MalformedLine(format!("{entry:?}")))?
+                    .parse()?;
+                let flags = AddRegFlags(parse_flags(fields.get(3))?);
+                Ok(RegistryEntry {
+                    root,
+                    subkey: fields.get(1).cloned().unwrap_or_default(),
+                    value_name: optional(fields.get(2)),
+                    flags,
+                    value: parse_value(flags, fields.get(4..).unwrap_or_default())?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl DelRegEntry {
+    /// Parses the lines of a section referenced by a `DelReg` directive
+    pub fn parse_section(
+        section: &InfSection,
+        strings: &StringTable,
+    ) -> Result<Vec<Self>, RegistryError> {
+        section
+            .entries()
+            .iter()
+            .map(|entry| {
+                let fields = expanded_fields(entry, strings)?;
+                let [root, subkey, ..] = fields.as_slice() else {
+                    return Err(RegistryError::MalformedLine(format!("{entry:?}")));
+                };
+                Ok(DelRegEntry {
+                    root: root.parse()?,
+                    subkey: subkey.clone(),
+                    value_name: optional(fields.get(2)),
+                    flags: parse_flags(fields.get(3))?,
+                    value: optional(fields.get(4)),
+                })
+            })
+            .collect()
+    }
+}
+
+impl BitRegEntry {
+    /// Parses the lines of a section referenced by a `BitReg` directive
+    pub fn parse_section(
+        section: &InfSection,
+        strings: &StringTable,
+    ) -> Result<Vec<Self>, RegistryError> {
+        section
+            .entries()
+            .iter()
+            .map(|entry| {
+                let fields = expanded_fields(entry, strings)?;
+                let [root, subkey, value_name, flags, byte_mask, byte_to_modify] =
+                    fields.as_slice()
+                else {
+                    return Err(RegistryError::MalformedLine(format!("{entry:?}")));
+                };
+                let invalid = || RegistryError::InvalidValue(fields.join(","));
+                Ok(BitRegEntry {
+                    root: root.parse()?,
+                    subkey: subkey.clone(),
+                    value_name: value_name.clone(),
+                    flags: parse_flags(Some(flags))?,
+                    byte_mask: parse_number(byte_mask)
+                        .and_then(|mask| u8::try_from(mask).ok())
+                        .ok_or_else(invalid)?,
+                    byte_to_modify: parse_number(byte_to_modify).ok_or_else(invalid)?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl WinInfFile {
+    pub(crate) fn registry_section(&self, name: &str) -> Result<&InfSection, RegistryError> {
+        self.section(name)
+            .ok_or_else(|| RegistryError::MissingSection(name.to_string()))
+    }
+
+    /// Returns the typed lines of a section referenced by an `AddReg` directive
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{RegistryRoot, RegistryValue, WinInfFile};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/sampledisplay.inf")).unwrap();
+    /// let entries = inf_file.add_reg("KDODSamp_EventLog_Inst.AddReg").unwrap();
+    /// assert_eq!(entries[1].root, RegistryRoot::Relative);
+    /// assert_eq!(entries[1].value, Some(RegistryValue::Dword(7)));
+    /// ```
+    pub fn add_reg(&self, section_name: &str) -> Result<Vec<RegistryEntry>, RegistryError> {
+        RegistryEntry::parse_section(self.registry_section(section_name)?, &self.string_table())
+    }
+
+    /// Returns the typed lines of a section referenced by a `DelReg` directive
+    pub fn del_reg(&self, section_name: &str) -> Result<Vec<DelRegEntry>, RegistryError> {
+        DelRegEntry::parse_section(self.registry_section(section_name)?, &self.string_table())
+    }
+
+    /// Returns the typed lines of a section referenced by a `BitReg` directive
+    pub fn bit_reg(&self, section_name: &str) -> Result<Vec<BitRegEntry>, RegistryError> {
+        BitRegEntry::parse_section(self.registry_section(section_name)?, &self.string_table())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> WinInfFile {
+        let mut inf_file = WinInfFile::default();
+        inf_file.parse_str(content).unwrap();
+        inf_file
+    }
+
+    #[test]
+    fn test_add_reg_value_types() {
+        let inf_file = parse(concat!(
+            "[Reg]\n",
+            "HKLM,Software\\Contoso,Name,,\"Contoso, Inc.\"\n",
+            "HKR,,Path,0x00020000,\"%%SystemRoot%%\\x.dll\"\n",
+            "HKR,,List,0x00010000,a,b,c\n",
+            "HKR,,Count,0x00010001,0x10\n",
+            "HKR,,Legacy,0x00010001,1,0,0,0\n",
+            "HKR,,Blob,1,01,02,\\\n",
+            "    0a,ff\n",
+            "HKR,,Quad,0x000b0001,01,00,00,00,00,00,00,00\n",
+            "HKR,Key,,0x00000010\n",
+            "HKCR,Key\n",
+        ));
+        let entries = inf_file.add_reg("Reg").unwrap();
+        assert_eq!(entries.len(), 9);
+        assert_eq!(entries[0].root, RegistryRoot::LocalMachine);
+        assert_eq!(entries[0].subkey, "Software\\Contoso");
+        assert_eq!(entries[0].value_name.as_deref(), Some("Name"));
+        assert_eq!(
+            entries[0].value,
+            Some(RegistryValue::String("Contoso, Inc.".to_string()))
+        );
+        assert_eq!(
+            entries[1].value,
+            Some(RegistryValue::ExpandString(
+                "%SystemRoot%\\x.dll".to_string()
+            ))
+        );
+        assert_eq!(
+            entries[2].value,
+            Some(RegistryValue::MultiString(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string()
+            ]))
+        );
+        assert_eq!(entries[3].value, Some(RegistryValue::Dword(16)));
+        assert_eq!(entries[4].value, Some(RegistryValue::Dword(1)));
+        assert_eq!(
+            entries[5].value,
+            Some(RegistryValue::Binary {
+                value_type: 3,
+                data: vec![0x01, 0x02, 0x0a, 0xff]
+            })
+        );
+        assert!(matches!(
+            entries[6].value,
+            Some(RegistryValue::Binary { value_type: 11, .. })
+        ));
+        assert!(entries[7].flags.contains(AddRegFlags::KEYONLY));
+        assert_eq!(entries[7].value_name, None);
+        assert_eq!(entries[7].value, None);
+        assert_eq!(entries[8].root, RegistryRoot::ClassesRoot);
+        assert_eq!(entries[8].value, None);
+    }
+
+    #[test]
+    fn test_add_reg_errors() {
+        let add_reg = |line: &str| parse(&format!("[Reg]\n{line}\n")).add_reg("Reg");
+        assert_eq!(
+            add_reg("HKXX,Key"),
+            Err(RegistryError::InvalidRoot("HKXX".to_string()))
+        );
+        assert_eq!(
+            add_reg("HKR,,Value,flags,1"),
+            Err(RegistryError::InvalidFlags("flags".to_string()))
+        );
+        assert!(matches!(
+            add_reg("HKR,,Value,0x00010001,1,2"),
+            Err(RegistryError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            add_reg("HKR,,Value,1,zz"),
+            Err(RegistryError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            add_reg("Key = HKR"),
+            Err(RegistryError::MalformedLine(_))
+        ));
+        assert!(matches!(
+            add_reg("HKR,,Value,,%Undefined%"),
+            Err(RegistryError::Strings(_))
+        ));
+        assert_eq!(
+            parse("[Reg]\n").add_reg("Other"),
+            Err(RegistryError::MissingSection("Other".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_del_reg_and_bit_reg() {
+        let inf_file = parse(concat!(
+            "[Del]\n",
+            "HKR,Sub\n",
+            "HKLM,Software\\Contoso,Filters,0x00018002,Filter1\n",
+            "[Bit]\n",
+            "HKR,,Flags,1,0x80,2\n",
+            "HKR,,Flags\n",
+        ));
+        let entries = inf_file.del_reg("Del").unwrap();
+        assert_eq!(entries[0].value_name, None);
+        assert_eq!(entries[1].flags, 0x18002);
+        assert_eq!(entries[1].value.as_deref(), Some("Filter1"));
+
+        assert!(matches!(
+            inf_file.bit_reg("Bit"),
+            Err(RegistryError::MalformedLine(_))
+        ));
+        let mut inf_file = parse("[Bit]\nHKR,,Flags,1,0x80,2\n");
+        let entries = inf_file.bit_reg("Bit").unwrap();
+        assert_eq!(entries[0].byte_mask, 0x80);
+        assert_eq!(entries[0].byte_to_modify, 2);
+        inf_file = parse("[Bit]\nHKR,,Flags,1,0x100,2\n");
+        assert!(inf_file.bit_reg("Bit").is_err());
+    }
+}
@@ -0,0 +1,511 @@
+use std::fmt;
+
+use crate::fields::parse_number;
+use crate::{
+    InfSection, InfValue, RegistryEntry, RegistryError, StringTable, StringsError, WinInfFile,
+};
+
+/// Errors that can occur while reading service installation sections
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ServiceError {
+    /// A section referenced by an `AddService` directive does not exist
+    #[error("Missing service section: {0}")]
+    MissingSection(String),
+    /// An `AddService` directive does not have a valid service name or flags
+    #[error("Invalid AddService directive: {0}")]
+    InvalidAddService(String),
+    /// A service-install section lacks a required directive
+    #[error("Missing {directive} directive in [{section}]")]
+    MissingDirective {
+        /// The service-install section
+        section: String,
+        /// The missing directive
+        directive: &'static str,
+    },
+    /// A directive of a service-install section has an invalid value
+    #[error("Invalid {directive} value: {value}")]
+    InvalidValue {
+        /// The directive with the invalid value
+        directive: &'static str,
+        /// The value as written in the INF file
+        value: String,
+    },
+    /// An `AddReg` section linked from a service could not be read
+    #[error("Invalid AddReg section: {0}")]
+    Registry(#[from] RegistryError),
+    /// A `%strkey%` token could not be expanded
+    #[error("Failed to expand string token: {0}")]
+    Strings(#[from] StringsError),
+}
+
+/// The flags of an `AddService` directive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ServiceInstallFlags(pub u32);
+
+impl ServiceInstallFlags {
+    /// `SPSVCINST_TAGTOFRONT`
+    pub const TAGTOFRONT: u32 = 0x0000_0001;
+    /// `SPSVCINST_ASSOCSERVICE`, the service is the function driver of the device
+    pub const ASSOCSERVICE: u32 = 0x0000_0002;
+    /// `SPSVCINST_DELETEEVENTLOGENTRY`
+    pub const DELETEEVENTLOGENTRY: u32 = 0x0000_0004;
+    /// `SPSVCINST_NOCLOBBER_DISPLAYNAME`
+    pub const NOCLOBBER_DISPLAYNAME: u32 = 0x0000_0008;
+    /// `SPSVCINST_NOCLOBBER_STARTTYPE`
+    pub const NOCLOBBER_STARTTYPE: u32 = 0x0000_0010;
+    /// `SPSVCINST_NOCLOBBER_ERRORCONTROL`
+    pub const NOCLOBBER_ERRORCONTROL: u32 = 0x0000_0020;
+    /// `SPSVCINST_NOCLOBBER_LOADORDERGROUP`
+    pub const NOCLOBBER_LOADORDERGROUP: u32 = 0x0000_0040;
+    /// `SPSVCINST_NOCLOBBER_DEPENDENCIES`
+    pub const NOCLOBBER_DEPENDENCIES: u32 = 0x0000_0080;
+    /// `SPSVCINST_NOCLOBBER_DESCRIPTION`
+    pub const NOCLOBBER_DESCRIPTION: u32 = 0x0000_0100;
+    /// `SPSVCINST_STOPSERVICE`
+    pub const STOPSERVICE: u32 = 0x0000_0200;
+    /// `SPSVCINST_CLOBBER_SECURITY`
+    pub const CLOBBER_SECURITY: u32 = 0x0000_0400;
+    /// `SPSVCINST_STARTSERVICE`
+    pub const STARTSERVICE: u32 = 0x0000_0800;
+    /// `SPSVCINST_NOCLOBBER_REQUIREDPRIVILEGES`
+    pub const NOCLOBBER_REQUIREDPRIVILEGES: u32 = 0x0000_1000;
+
+    /// Returns `true` if all bits of `flag` are set
+    pub fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+}
+
+/// The `ServiceType` of a service, one of the `SERVICE_*` type constants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServiceType {
+    /// `SERVICE_KERNEL_DRIVER`, `0x1`
+    KernelDriver,
+    /// `SERVICE_FILE_SYSTEM_DRIVER`, `0x2`
+    FileSystemDriver,
+    /// `SERVICE_ADAPTER`, `0x4`
+    Adapter,
+    /// `SERVICE_RECOGNIZER_DRIVER`, `0x8`
+    RecognizerDriver,
+    /// `SERVICE_WIN32_OWN_PROCESS`, `0x10`
+    Win32OwnProcess,
+    /// `SERVICE_WIN32_SHARE_PROCESS`, `0x20`
+    Win32ShareProcess,
+    /// Any other combination of type bits
+    Other(u32),
+}
+
+impl From<u32> for ServiceType {
+    fn from(value: u32) -> Self {
+        match value {
+            0x1 => ServiceType::KernelDriver,
+            0x2 => ServiceType::FileSystemDriver,
+            0x4 => ServiceType::Adapter,
+            0x8 => ServiceType::RecognizerDriver,
+            0x10 => ServiceType::Win32OwnProcess,
+            0x20 => ServiceType::Win32ShareProcess,
+            other => ServiceType::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for ServiceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceType::KernelDriver => write!(f, "SERVICE_KERNEL_DRIVER"),
+            ServiceType::FileSystemDriver => write!(f, "SERVICE_FILE_SYSTEM_DRIVER"),
+            ServiceType::Adapter => write!(f, "SERVICE_ADAPTER"),
+            ServiceType::RecognizerDriver => write!(f, "SERVICE_RECOGNIZER_DRIVER"),
+            ServiceType::Win32OwnProcess => write!(f, "SERVICE_WIN32_OWN_PROCESS"),
+            ServiceType::Win32ShareProcess => write!(f, "SERVICE_WIN32_SHARE_PROCESS"),
+            ServiceType::Other(value) => write!(f, "{value:#x}"),
+        }
+    }
+}
+
+/// The `StartType` of a service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StartType {
+    /// `SERVICE_BOOT_START`, `0x0`
+    Boot,
+    /// `SERVICE_SYSTEM_START`, `0x1`
+    System,
+    /// `SERVICE_AUTO_START`, `0x2`
+    Auto,
+    /// `SERVICE_DEMAND_START`, `0x3`
+    Demand,
+    /// `SERVICE_DISABLED`, `0x4`
+    Disabled,
+}
+
+impl TryFrom<u32> for StartType {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(StartType::Boot),
+            1 => Ok(StartType::System),
+            2 => Ok(StartType::Auto),
+            3 => Ok(StartType::Demand),
+            4 => Ok(StartType::Disabled),
+            other => Err(other),
+        }
+    }
+}
+
+impl fmt::Display for StartType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartType::Boot => write!(f, "SERVICE_BOOT_START"),
+            StartType::System => write!(f, "SERVICE_SYSTEM_START"),
+            StartType::Auto => write!(f, "SERVICE_AUTO_START"),
+            StartType::Demand => write!(f, "SERVICE_DEMAND_START"),
+            StartType::Disabled => write!(f, "SERVICE_DISABLED"),
+        }
+    }
+}
+
+/// The `ErrorControl` level of a service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorControl {
+    /// `SERVICE_ERROR_IGNORE`, `0x0`
+    Ignore,
+    /// `SERVICE_ERROR_NORMAL`, `0x1`
+    Normal,
+    /// `SERVICE_ERROR_SEVERE`, `0x2`
+    Severe,
+    /// `SERVICE_ERROR_CRITICAL`, `0x3`
+    Critical,
+}
+
+impl TryFrom<u32> for ErrorControl {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ErrorControl::Ignore),
+            1 => Ok(ErrorControl::Normal),
+            2 => Ok(ErrorControl::Severe),
+            3 => Ok(ErrorControl::Critical),
+            other => Err(other),
+        }
+    }
+}
+
+impl fmt::Display for ErrorControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorControl::Ignore => write!(f, "SERVICE_ERROR_IGNORE"),
+            ErrorControl::Normal => write!(f, "SERVICE_ERROR_NORMAL"),
+            ErrorControl::Severe => write!(f, "SERVICE_ERROR_SEVERE"),
+            ErrorControl::Critical => write!(f, "SERVICE_ERROR_CRITICAL"),
+        }
+    }
+}
+
+/// The event-log section of an `AddService` directive
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventLogInstall {
+    /// The name of the event-log-install section
+    pub section: String,
+    /// The event log, `System` unless the directive names another one
+    pub log_type: String,
+    /// The event source name, the service name unless the directive names another one
+    pub event_name: String,
+    /// The registry lines of the sections referenced by its `AddReg` directives
+    pub add_reg: Vec<RegistryEntry>,
+}
+
+/// A service installed by an `AddService` directive
+///
+/// `AddService = ServiceName, [flags], service-install-section
+/// [, event-log-install-section [, [EventLogType] [, EventName]]]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceInstall {
+    /// The name of the service, empty when the directive associates a null driver
+    pub name: String,
+    /// The `SPSVCINST_*` flags
+    pub flags: ServiceInstallFlags,
+    /// The name of the service-install section
+    pub install_section: Option<String>,
+    /// The `DisplayName` of the service
+    pub display_name: Option<String>,
+    /// The `Description` of the service
+    pub description: Option<String>,
+    /// The `ServiceType` of the service, present when there is a service-install section
+    pub service_type: Option<ServiceType>,
+    /// The `StartType` of the service, present when there is a service-install section
+    pub start_type: Option<StartType>,
+    /// The `ErrorControl` of the service, present when there is a service-install section
+    pub error_control: Option<ErrorControl>,
+    /// The `ServiceBinary` path, with `%strkey%` tokens expanded and DIRIDs kept
+    pub service_binary: Option<String>,
+    /// The `LoadOrderGroup` of the service
+    pub load_order_group: Option<String>,
+    /// The `Dependencies` of the service, load order groups are prefixed with `+`
+    pub dependencies: Vec<String>,
+    /// The registry lines of the sections referenced by `AddReg` in the service-install section
+    pub add_reg: Vec<RegistryEntry>,
+    /// The event-log section, if the directive names one
+    pub event_log: Option<EventLogInstall>,
+}
+
+/// Expands the first field of a directive
+fn service_directive(
+    section: &InfSection,
+    directive: &str,
+    strings: &StringTable,
+) -> Result<Option<String>, ServiceError> {
+    section
+        .get(directive)
+        .and_then(|value| value.field(0))
+        .map(|value| strings.expand(value))
+        .transpose()
+        .map_err(ServiceError::from)
+}
+
+fn service_number(
+    section: &InfSection,
+    directive: &'static str,
+    strings: &StringTable,
+) -> Result<u32, ServiceError> {
+    let value = service_directive(section, directive, strings)?.ok_or_else(|| {
+        ServiceError::MissingDirective {
+            section: section.name().to_string(),
+            directive,
+        }
+    })?;
+    parse_number(&value).ok_or(ServiceError::InvalidValue { directive, value })
+}
+
+impl WinInfFile {
+    /// Returns the registry lines of all sections named by `AddReg` directives of a section
+    fn linked_add_reg(
+        &self,
+        section: &InfSection,
+        strings: &StringTable,
+    ) -> Result<Vec<RegistryEntry>, ServiceError> {
+        let mut entries = Vec::new();
+        for value in section.get_all("AddReg") {
+            for name in value.fields().into_iter().filter(|name| !name.is_empty()) {
+                let section = self.registry_section(name)?;
+                entries.extend(RegistryEntry::parse_section(section, strings)?);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn service_section(&self, name: &str) -> Result<&InfSection, ServiceError> {
+        self.section(name)
+            .ok_or_else(|| ServiceError::MissingSection(name.to_string()))
+    }
+
+    fn service_install(
+        &self,
+        value: &InfValue,
+        strings: &StringTable,
+    ) -> Result<ServiceInstall, ServiceError> {
+        let fields = value
+            .fields()
+            .into_iter()
+            .map(|field| strings.expand(field))
+            .collect::<Result<Vec<_>, _>>()?;
+        let field = |idx: usize| fields.get(idx).filter(|f| !f.is_empty());
+        let invalid = || ServiceError::InvalidAddService(value.to_string());
+
+        let name = fields.first().cloned().ok_or_else(invalid)?;
+        let flags = match field(1) {
+            Some(flags) => parse_number(flags).ok_or_else(invalid)?,
+            None => 0,
+        };
+        let mut service = ServiceInstall {
+            name,
+            flags: ServiceInstallFlags(flags),
+            install_section: field(2).cloned(),
+            display_name: None,
+            description: None,
+            service_type: None,
+            start_type: None,
+            error_control: None,
+            service_binary: None,
+            load_order_group: None,
+            dependencies: Vec::new(),
+            add_reg: Vec::new(),
+            event_log: None,
+        };
+
+        if let Some(section_name) = &service.install_section {
+            let section = self.service_section(section_name)?;
+            service.display_name = service_directive(section, "DisplayName", strings)?;
+            service.description = service_directive(section, "Description", strings)?;
+            service.service_type = Some(service_number(section, "ServiceType", strings)?.into());
+            let start_type = service_number(section, "StartType", strings)?;
+            service.start_type = Some(StartType::try_from(start_type).map_err(|value| {
+                ServiceError::InvalidValue {
+                    directive: "StartType",
+                    value: value.to_string(),
+                }
+            })?);
+            let error_control = service_number(section, "ErrorControl", strings)?;
+            service.error_control =
+                Some(ErrorControl::try_from(error_control).map_err(|value| {
+                    ServiceError::InvalidValue {
+                        directive: "ErrorControl",
+                        value: value.to_string(),
+                    }
+                })?);
+            service.service_binary = Some(
+                service_directive(section, "ServiceBinary", strings)?.ok_or_else(|| {
+                    ServiceError::MissingDirective {
+                        section: section.name().to_string(),
+                        directive: "ServiceBinary",
+                    }
+                })?,
+            );
+            service.load_order_group = service_directive(section, "LoadOrderGroup", strings)?;
+            for value in section.get_all("Dependencies") {
+                for dependency in value.fields().into_iter().filter(|d| !d.is_empty()) {
+                    service.dependencies.push(strings.expand(dependency)?);
+                }
+            }
+            service.add_reg = self.linked_add_reg(section, strings)?;
+        }
+
+        if let Some(section_name) = field(3) {
+            let section = self.service_section(section_name)?;
+            service.event_log = Some(EventLogInstall {
+                section: section_name.clone(),
+                log_type: field(4).cloned().unwrap_or_else(|| "System".to_string()),
+                event_name: field(5).cloned().unwrap_or_else(|| service.name.clone()),
+                add_reg: self.linked_add_reg(section, strings)?,
+            });
+        }
+        Ok(service)
+    }
+
+    /// Returns the services installed by the `AddService` directives of a section
+    ///
+    /// This is usually the `.Services` companion of a DDInstall section, see
+    /// [`WinInfFile::ddinstall`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Arch, Platform, ServiceInstallFlags, StartType, WinInfFile};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/AudioCodec.inf")).unwrap();
+    /// let install = inf_file
+    ///     .ddinstall("Audio_Device", &Platform::new(Arch::Amd64, 10, 0))
+    ///     .unwrap();
+    /// let services = inf_file.add_services(install.services.unwrap().name()).unwrap();
+    /// assert_eq!(services[0].name, "AudioCodec");
+    /// assert!(services[0].flags.contains(ServiceInstallFlags::ASSOCSERVICE));
+    /// assert_eq!(services[0].start_type, Some(StartType::Demand));
+    /// ```
+    pub fn add_services(&self, section_name: &str) -> Result<Vec<ServiceInstall>, ServiceError> {
+        let strings = self.string_table();
+        self.service_section(section_name)?
+            .get_all("AddService")
+            .map(|value| self.service_install(value, &strings))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RegistryValue;
+
+    fn parse(content: &str) -> WinInfFile {
+        let mut inf_file = WinInfFile::default();
+        inf_file.parse_str(content).unwrap();
+        inf_file
+    }
+
+    #[test]
+    fn test_add_services() {
+        let inf_file = parse(concat!(
+            "[Inst.Services]\n",
+            "AddService = Svc, %FLAGS%, Svc_Inst, Svc_EventLog, Application, SvcEvents\n",
+            "AddService = , 0x2\n",
+            "[Svc_Inst]\n",
+            "DisplayName = %SvcName%\n",
+            "ServiceType = 0x110\n",
+            "StartType = 2\n",
+            "ErrorControl = 3\n",
+            "ServiceBinary = %11%\\svc.exe\n",
+            "LoadOrderGroup = Extended Base\n",
+            "Dependencies = +NDIS, Tcpip\n",
+            "AddReg = Svc_AddReg\n",
+            "[Svc_AddReg]\n",
+            "HKR,Parameters,Level,0x00010001,4\n",
+            "[Svc_EventLog]\n",
+            "[Strings]\n",
+            "FLAGS = 0x802\n",
+            "SvcName = \"Contoso Service\"\n",
+        ));
+        let services = inf_file.add_services("Inst.Services").unwrap();
+        assert_eq!(services.len(), 2);
+        let service = &services[0];
+        assert!(service.flags.contains(ServiceInstallFlags::ASSOCSERVICE));
+        assert!(service.flags.contains(ServiceInstallFlags::STARTSERVICE));
+        assert_eq!(service.display_name.as_deref(), Some("Contoso Service"));
+        assert_eq!(service.service_type, Some(ServiceType::Other(0x110)));
+        assert_eq!(service.start_type, Some(StartType::Auto));
+        assert_eq!(service.error_control, Some(ErrorControl::Critical));
+        assert_eq!(service.service_binary.as_deref(), Some("%11%\\svc.exe"));
+        assert_eq!(service.load_order_group.as_deref(), Some("Extended Base"));
+        assert_eq!(service.dependencies, vec!["+NDIS", "Tcpip"]);
+        assert_eq!(service.add_reg[0].value, Some(RegistryValue::Dword(4)));
+        let event_log = service.event_log.as_ref().unwrap();
+        assert_eq!(event_log.log_type, "Application");
+        assert_eq!(event_log.event_name, "SvcEvents");
+        assert!(event_log.add_reg.is_empty());
+
+        assert_eq!(services[1].name, "");
+        assert_eq!(services[1].install_section, None);
+        assert_eq!(services[1].service_type, None);
+    }
+
+    #[test]
+    fn test_add_services_errors() {
+        let add_services = |inst: &str| {
+            parse(&format!(
+                "[Inst.Services]\nAddService = Svc, 2, Svc_Inst\n[Svc_Inst]\n{inst}"
+            ))
+            .add_services("Inst.Services")
+        };
+        assert_eq!(
+            add_services("StartType = 3\nErrorControl = 1\nServiceBinary = x.sys\n"),
+            Err(ServiceError::MissingDirective {
+                section: "Svc_Inst".to_string(),
+                directive: "ServiceType",
+            })
+        );
+        assert_eq!(
+            add_services(
+                "ServiceType = 1\nStartType = 9\nErrorControl = 1\nServiceBinary = x.sys\n"
+            ),
+            Err(ServiceError::InvalidValue {
+                directive: "StartType",
+                value: "9".to_string(),
+            })
+        );
+        assert!(matches!(
+            add_services(
+                "ServiceType = 1\nStartType = 3\nErrorControl = 1\nServiceBinary = x.sys\nAddReg = Missing\n"
+            ),
+            Err(ServiceError::Registry(RegistryError::MissingSection(_)))
+        ));
+        assert_eq!(
+            parse("[Inst.Services]\nAddService = Svc, 2, Svc_Inst\n").add_services("Inst.Services"),
+            Err(ServiceError::MissingSection("Svc_Inst".to_string()))
+        );
+        assert!(matches!(
+            parse("[Inst.Services]\nAddService = Svc, flags\n").add_services("Inst.Services"),
+            Err(ServiceError::InvalidAddService(_))
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::fields::parse_number;
+use crate::{Arch, InfEntry, InfValue, StringTable, StringsError, WinInfFile};
+
+/// Errors that can occur while reading the source media sections
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SourceMediaError {
+    /// A `[SourceDisksNames]` entry does not have a numeric disk ID
+    #[error("Invalid SourceDisksNames entry: {0}")]
+    InvalidDiskEntry(String),
+    /// A `[SourceDisksFiles]` entry does not have a numeric disk ID or size
+    #[error("Invalid SourceDisksFiles entry: {0}")]
+    InvalidFileEntry(String),
+    /// A file is on a disk that `[SourceDisksNames]` does not define
+    #[error("File {file} is on undefined disk {disk_id}")]
+    UndefinedDisk {
+        /// The file name
+        file: String,
+        /// The disk ID of the file
+        disk_id: u32,
+    },
+    /// A `%strkey%` token could not be expanded
+    #[error("Failed to expand string token: {0}")]
+    Strings(#[from] StringsError),
+}
+
+/// A source disk from `[SourceDisksNames]`
+///
+/// `diskid = disk-description[,[tag-or-cab-file],[unused],[path],[flags][,tag-file]]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDisk {
+    /// The disk ID files refer to
+    pub id: u32,
+    /// The description shown to the user
+    pub description: String,
+    /// The tag file or cabinet file of the disk
+    pub tag_or_cab_file: Option<String>,
+    /// The directory of the disk, relative to the INF file
+    pub path: Option<String>,
+    /// The flags of the disk, `0` when absent
+    pub flags: u32,
+    /// The section the disk is defined in, e.g. `SourceDisksNames.amd64`
+    pub section: String,
+}
+
+/// A payload file from `[SourceDisksFiles]`
+///
+/// `filename = diskid[,[subdir][,size]]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// The file name
+    pub name: String,
+    /// The disk the file is on
+    pub disk_id: u32,
+    /// The directory of the file, relative to the disk path
+    pub subdir: Option<String>,
+    /// The uncompressed size of the file in bytes
+    pub size: Option<u32>,
+    /// The section the file is listed in, e.g. `SourceDisksFiles.amd64`
+    pub section: String,
+}
+
+/// The source disks and payload files of a driver package for an architecture
+///
+/// Entries of the architecture-decorated sections, such as `[SourceDisksFiles.amd64]`,
+/// replace undecorated entries with the same disk ID or file name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceManifest {
+    /// The source disks, in file order
+    pub disks: Vec<SourceDisk>,
+    /// The payload files, in file order
+    pub files: Vec<SourceFile>,
+}
+
+impl SourceManifest {
+    /// Returns the disk with the given ID
+    pub fn disk(&self, id: u32) -> Option<&SourceDisk> {
+        self.disks.iter().find(|disk| disk.id == id)
+    }
+
+    /// Returns the file with the given name, ignoring case
+    pub fn file(&self, name: &str) -> Option<&SourceFile> {
+        self.files
+            .iter()
+            .find(|file| file.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the path of a file relative to the directory of the INF file
+    ///
+    /// The path joins the disk path, the file subdirectory and the file name with `\`.
+    /// Returns `None` if the file is not listed or its disk is not defined.
+    pub fn source_path(&self, name: &str) -> Option<String> {
+        let file = self.file(name)?;
+        let disk = self.disk(file.disk_id)?;
+        let path = [
+            disk.path.as_deref(),
+            file.subdir.as_deref(),
+            Some(&file.name),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|part| part.trim_matches('\\'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+        Some(path.join("\\"))
+    }
+}
+
+/// Returns the expanded fields of an entry, with the key first
+fn source_fields(entry: &InfEntry, strings: &StringTable) -> Result<Vec<String>, StringsError> {
+    let value = entry.value().map(InfValue::fields).unwrap_or_default();
+    entry
+        .key()
+        .into_iter()
+        .chain(value)
+        .map(|field| strings.expand(field))
+        .collect()
+}
+
+impl WinInfFile {
+    /// Returns the source disks and payload files for an architecture
+    ///
+    /// Without an architecture only the undecorated sections are read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{Arch, WinInfFile};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/AudioCodec.inf")).unwrap();
+    /// let manifest = inf_file.source_manifest(Some(Arch::Amd64)).unwrap();
+    /// assert_eq!(manifest.disk(1).unwrap().description, "AudioCodec Installation Disk");
+    /// assert_eq!(manifest.source_path("audiocodec.sys").unwrap(), "AudioCodec.sys");
+    /// ```
+    pub fn source_manifest(&self, arch: Option<Arch>) -> Result<SourceManifest, SourceMediaError> {
+        let mut section_names = vec![String::new()];
+        if let Some(arch) = arch {
+            section_names.push(format!(".{arch}"));
+        }
+
+        let strings = self.string_table();
+        let mut manifest = SourceManifest::default();
+        for suffix in &section_names {
+            let Some(section) = self.section(&format!("SourceDisksNames{suffix}")) else {
+                continue;
+            };
+            for entry in section.entries() {
+                let fields = source_fields(entry, &strings)?;
+                let field = |idx: usize| fields.get(idx).filter(|f| !f.is_empty()).cloned();
+                let invalid = || SourceMediaError::InvalidDiskEntry(format!("{entry:?}"));
+                let disk = SourceDisk {
+                    id: field(0)
+                        .and_then(|id| parse_number(&id))
+                        .ok_or_else(invalid)?,
+                    description: field(1).unwrap_or_default(),
+                    tag_or_cab_file: field(2),
+                    path: field(4),
+                    flags: match field(5) {
+                        Some(flags) => parse_number(&flags).ok_or_else(invalid)?,
+                        None => 0,
+                    },
+                    section: section.name().to_string(),
+                };
+                match manifest.disks.iter_mut().find(|d| d.id == disk.id) {
+                    Some(existing) => *existing = disk,
+                    None => manifest.disks.push(disk),
+                }
+            }
+        }
+
+        for suffix in &section_names {
+            let Some(section) = self.section(&format!("SourceDisksFiles{suffix}")) else {
+                continue;
+            };
+            for entry in section.entries() {
+                let fields = source_fields(entry, &strings)?;
+                let field = |idx: usize| fields.get(idx).filter(|f| !f.is_empty()).cloned();
+                let invalid = || SourceMediaError::InvalidFileEntry(format!("{entry:?}"));
+                let file = SourceFile {
+                    name: field(0).ok_or_else(invalid)?,
+                    disk_id: field(1)
+                        .and_then(|id| parse_number(&id))
+                        .ok_or_else(invalid)?,
+                    subdir: field(2),
+                    size: match field(3) {
+                        Some(size) => Some(parse_number(&size).ok_or_else(invalid)?),
+                        None => None,
+                    },
+                    section: section.name().to_string(),
+                };
+                match manifest
+                    .files
+                    .iter_mut()
+                    .find(|f| f.name.eq_ignore_ascii_case(&file.name))
+                {
+                    Some(existing) => *existing = file,
+                    None => manifest.files.push(file),
+                }
+            }
+        }
+
+        if let Some(file) = manifest
+            .files
+            .iter()
+            .find(|file| manifest.disk(file.disk_id).is_none())
+        {
+            return Err(SourceMediaError::UndefinedDisk {
+                file: file.name.clone(),
+                disk_id: file.disk_id,
+            });
+        }
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> WinInfFile {
+        let mut inf_file = WinInfFile::default();
+        inf_file.parse_str(content).unwrap();
+        inf_file
+    }
+
+    #[test]
+    fn test_source_manifest_decorations() {
+        let inf_file = parse(concat!(
+            "[SourceDisksNames]\n",
+            "1 = %Disk%,,,\\x86\n",
+            "2 = \"Tools\",tools.cab,,tools,0x10\n",
+            "[SourceDisksNames.amd64]\n",
+            "1 = %Disk%,,,\\amd64\n",
+            "[SourceDisksFiles]\n",
+            "driver.sys = 1,,1024\n",
+            "tool.exe = 2,bin\n",
+            "[SourceDisksFiles.amd64]\n",
+            "Driver.sys = 1,sub,2048\n",
+            "[Strings]\n",
+            "Disk = \"Install Disk\"\n",
+        ));
+        let manifest = inf_file.source_manifest(None).unwrap();
+        assert_eq!(
+            manifest.source_path("driver.sys").unwrap(),
+            "x86\\driver.sys"
+        );
+        assert_eq!(manifest.file("driver.sys").unwrap().size, Some(1024));
+        assert_eq!(
+            manifest.source_path("tool.exe").unwrap(),
+            "tools\\bin\\tool.exe"
+        );
+        let tools = manifest.disk(2).unwrap();
+        assert_eq!(tools.tag_or_cab_file.as_deref(), Some("tools.cab"));
+        assert_eq!(tools.flags, 0x10);
+
+        let manifest = inf_file.source_manifest(Some(Arch::Amd64)).unwrap();
+        assert_eq!(manifest.disks.len(), 2);
+        assert_eq!(manifest.disk(1).unwrap().section, "SourceDisksNames.amd64");
+        assert_eq!(manifest.disk(1).unwrap().description, "Install Disk");
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(
+            manifest.source_path("DRIVER.SYS").unwrap(),
+            "amd64\\sub\\Driver.sys"
+        );
+        assert_eq!(manifest.file("driver.sys").unwrap().size, Some(2048));
+        assert_eq!(manifest.source_path("missing.sys"), None);
+    }
+
+    #[test]
+    fn test_source_manifest_errors() {
+        assert!(matches!(
+            parse("[SourceDisksNames]\nx = Disk\n").source_manifest(None),
+            Err(SourceMediaError::InvalidDiskEntry(_))
+        ));
+        assert!(matches!(
+            parse("[SourceDisksNames]\n1 = Disk\n[SourceDisksFiles]\na.sys = 1,,big\n")
+                .source_manifest(None),
+            Err(SourceMediaError::InvalidFileEntry(_))
+        ));
+        assert_eq!(
+            parse("[SourceDisksNames]\n1 = Disk\n[SourceDisksFiles]\na.sys = 2\n")
+                .source_manifest(None),
+            Err(SourceMediaError::UndefinedDisk {
+                file: "a.sys".to_string(),
+                disk_id: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+/// A position in the content of an INF file
+///
+/// Lines and columns start at 1; columns count characters. The byte offset counts bytes of
+/// the content as it was read, before decoding, so it includes the byte order mark and
+/// advances by two or four bytes per character in UTF-16 files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line number, starting at 1
+    pub line: usize,
+    /// The column in characters, starting at 1
+    pub column: usize,
+    /// The byte offset from the start of the content
+    pub offset: usize,
+}
+
+impl Position {
+    pub(crate) fn new(line: usize, column: usize, offset: usize) -> Self {
+        Position {
+            line,
+            column,
+            offset,
+        }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::new(1, 1, 0)
+    }
+}
+
+/// Formats the position as `line:column`
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A range of the content of an INF file, from `start` up to but not including `end`
+///
+/// The span of an entry continued with `\` ends on the last line of the entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    /// The position of the first character
+    pub start: Position,
+    /// The position just after the last character
+    pub end: Position,
+}
+
+impl Span {
+    /// Returns the number of bytes the span covers
+    pub fn byte_len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    /// Returns `true` if the span contains the byte offset
+    pub fn contains(&self, offset: usize) -> bool {
+        (self.start.offset..self.end.offset).contains(&offset)
+    }
+}
+
+/// Formats the span by its start, as `line:column`
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
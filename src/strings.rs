@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+
+use crate::{InfEntry, InfSection, InfValue, WinInfFile};
+
+/// Errors that can occur while expanding `%strkey%` tokens
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum StringsError {
+    /// A `%strkey%` token has no definition in the `[Strings]` section
+    #[error("Undefined string token: %{0}%")]
+    UndefinedToken(String),
+}
+
+/// The string tokens defined in a `[Strings]` section
+///
+/// Token names are matched ignoring case, as Windows does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StringTable {
+    strings: HashMap<String, String>,
+}
+
+/// A piece of text split at `%` signs
+enum Token<'a> {
+    Text(&'a str),
+    /// `%%`, an escaped percent sign
+    Percent,
+    /// `%name%`
+    Key(&'a str),
+}
+
+/// Splits text into literal parts, `%%` escapes and `%strkey%` tokens
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('%') {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(0) => {
+                tokens.push(Token::Percent);
+                rest = &after[1..];
+            }
+            Some(end) => {
+                tokens.push(Token::Key(&after[..end]));
+                rest = &after[end + 1..];
+            }
+            None => {
+                // a lone percent sign is kept as it is
+                tokens.push(Token::Text(&rest[start..]));
+                rest = "";
+            }
+        }
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    tokens
+}
+
+/// Numeric tokens such as `%13%` refer to DIRIDs, not to strings
+pub(crate) fn is_dirid_token(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns the string a `[Strings]` value written as `raw` stands for
+///
+/// Commas are part of the string, so the text is kept as it is written. Only a value
+/// that is quoted as a whole loses its quotes, with `""` standing for a double quote.
+fn string_value(raw: &str) -> String {
+    match raw.strip_prefix('"').and_then(|raw| raw.strip_suffix('"')) {
+        Some(inner) if !inner.replace("\"\"", "").contains('"') => inner.replace("\"\"", "\""),
+        _ => raw.to_string(),
+    }
+}
+
+impl StringTable {
+    /// Builds a string table from the entries of a `[Strings]` section
+    ///
+    /// When a token is defined more than once, the first definition wins.
+    pub fn from_section(section: &InfSection) -> Self {
+        let mut table = StringTable::default();
+        table.extend_from_section(section);
+        table
+    }
+
+    /// Adds the tokens of a `[Strings]` section that are not defined yet
+    ///
+    /// Parsed values are taken as they are written, so `Desc = Foo, Bar` defines
+    /// `Foo, Bar`.
+    pub(crate) fn extend_from_section(&mut self, section: &InfSection) {
+        for (idx, entry) in section.entries().iter().enumerate() {
+            if let InfEntry::KeyValue(key, value) = entry {
+                let value = match section.raw_value(idx) {
+                    Some(raw) => string_value(raw),
+                    None => value
+                        .as_ref()
+                        .map(|v| v.fields().join(","))
+                        .unwrap_or_default(),
+                };
+                self.strings
+                    .entry(key.to_ascii_lowercase())
+                    .or_insert(value);
+            }
+        }
+    }
+
+    /// Returns the number of defined tokens
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if no tokens are defined
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Returns the string defined for the token `key`, without the surrounding `%`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings
+            .get(&key.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Expands all `%strkey%` tokens in `text`
+    ///
+    /// `%%` is replaced by a single `%`. Numeric tokens such as `%13%` are DIRIDs and
+    /// are kept as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse_str("[Strings]\nProviderName = \"Contoso\"\n").unwrap();
+    /// let strings = inf_file.string_table();
+    /// assert_eq!(strings.expand("%ProviderName% 100%%").unwrap(), "Contoso 100%");
+    /// assert!(strings.expand("%Missing%").is_err());
+    /// ```
+    pub fn expand(&self, text: &str) -> Result<String, StringsError> {
+        let mut expanded = String::with_capacity(text.len());
+        for token in tokenize(text) {
+            match token {
+                Token::Text(t) => expanded.push_str(t),
+                Token::Percent => expanded.push('%'),
+                Token::Key(key) if is_dirid_token(key) => {
+                    expanded.push('%');
+                    expanded.push_str(key);
+                    expanded.push('%');
+                }
+                Token::Key(key) => match self.get(key) {
+                    Some(value) => expanded.push_str(value),
+                    None => return Err(StringsError::UndefinedToken(key.to_string())),
+                },
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Returns the `%strkey%` tokens in `text` that have no definition
+    pub fn undefined_tokens(&self, text: &str) -> Vec<String> {
+        tokenize(text)
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Key(key) if !is_dirid_token(key) && self.get(key).is_none() => {
+                    Some(key.to_string())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Expands the tokens in every field of a value
+    pub fn expand_value(&self, value: &InfValue) -> Result<InfValue, StringsError> {
+        Ok(match value {
+            InfValue::Raw(v) => InfValue::Raw(self.expand(v)?),
+            InfValue::CommaSeparated(values) => InfValue::CommaSeparated(
+                values
+                    .iter()
+                    .map(|v| self.expand(v))
+                    .collect::<Result<_, _>>()?,
+            ),
+            InfValue::List(values) => InfValue::List(
+                values
+                    .iter()
+                    .map(|v| self.expand(v))
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+
+    /// Expands the tokens in the key and the value of an entry
+    pub fn expand_entry(&self, entry: &InfEntry) -> Result<InfEntry, StringsError> {
+        Ok(match entry {
+            InfEntry::KeyValue(key, value) => InfEntry::KeyValue(
+                self.expand(key)?,
+                value.as_ref().map(|v| self.expand_value(v)).transpose()?,
+            ),
+            InfEntry::OnlyValue(value) => InfEntry::OnlyValue(self.expand_value(value)?),
+        })
+    }
+}
+
+/// Returns the language ID of a `[Strings.LLLL]` section name
+fn strings_section_lang_id(name: &str) -> Option<u16> {
+    let (prefix, lang_id) = name.split_once('.')?;
+    if !prefix.eq_ignore_ascii_case("Strings")
+        || lang_id.is_empty()
+        || lang_id.len() > 4
+        || !lang_id.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    u16::from_str_radix(lang_id, 16).ok()
+}
+
+/// Returns the primary language part of a language ID, e.g. `0x09` for `0x0409`
+fn primary_lang_id(lang_id: u16) -> u16 {
+    lang_id & 0x3ff
+}
+
+impl WinInfFile {
+    /// Returns the string tokens defined in the `[Strings]` section
+    pub fn string_table(&self) -> StringTable {
+        self.section("Strings")
+            .map(StringTable::from_section)
+            .unwrap_or_default()
+    }
+
+    /// Returns the language IDs of the localized `[Strings.LLLL]` sections, in file order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file
+    ///     .parse_str("[Strings]\nA=a\n[Strings.0409]\nA=en\n[Strings.0407]\nA=de\n")
+    ///     .unwrap();
+    /// assert_eq!(inf_file.locales(), vec![0x0409, 0x0407]);
+    /// ```
+    pub fn locales(&self) -> Vec<u16> {
+        self.sections
+            .names()
+            .filter_map(strings_section_lang_id)
+            .collect()
+    }
+
+    /// Returns the localized strings section Windows would pick for a language ID
+    ///
+    /// The lookup follows Windows: a section for the exact language ID (e.g.
+    /// `[Strings.0407]` for `0x0407`), then one for the primary language with a neutral
+    /// sublanguage (`[Strings.0007]`), then any section for the same primary language.
+    /// Returns `None` if there is no localized match, in which case only the
+    /// undecorated `[Strings]` section applies.
+    pub fn localized_strings_section(&self, lang_id: u16) -> Option<&InfSection> {
+        let primary = primary_lang_id(lang_id);
+        let candidates: Vec<(u16, &InfSection)> = self
+            .sections
+            .values()
+            .filter_map(|s| strings_section_lang_id(s.name()).map(|id| (id, s)))
+            .collect();
+
+        candidates
+            .iter()
+            .find(|(id, _)| *id == lang_id)
+            .or_else(|| candidates.iter().find(|(id, _)| *id == primary))
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .find(|(id, _)| primary_lang_id(*id) == primary)
+            })
+            .map(|(_, section)| *section)
+    }
+
+    /// Returns the string tokens to use for a language ID
+    ///
+    /// Tokens come from the section chosen by
+    /// [`WinInfFile::localized_strings_section`]; tokens it does not define fall back
+    /// to the undecorated `[Strings]` section.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file
+    ///     .parse_str("[Strings]\nDesc=Device\nMfg=Contoso\n[Strings.0407]\nDesc=Gerät\n")
+    ///     .unwrap();
+    /// let strings = inf_file.string_table_for_locale(0x0807);
+    /// assert_eq!(strings.expand("%Desc% (%Mfg%)").unwrap(), "Gerät (Contoso)");
+    /// ```
+    pub fn string_table_for_locale(&self, lang_id: u16) -> StringTable {
+        let mut table = StringTable::default();
+        if let Some(section) = self.localized_strings_section(lang_id) {
+            table.extend_from_section(section);
+        }
+        if let Some(section) = self.section("Strings") {
+            table.extend_from_section(section);
+        }
+        table
+    }
+
+    /// Expands all `%strkey%` tokens in `text` using the `[Strings]` section
+    ///
+    /// See [`StringTable::expand`] for the expansion rules.
+    pub fn expand(&self, text: &str) -> Result<String, StringsError> {
+        self.string_table().expand(text)
+    }
+
+    /// Returns a copy of `entry` with all `%strkey%` tokens expanded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{InfEntry, InfValue, WinInfFile};
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file
+    ///     .parse_str("[Version]\nProvider=%ProviderName%\n[Strings]\nProviderName=Contoso\n")
+    ///     .unwrap();
+    /// let entry = &inf_file.section("Version").unwrap().entries()[0];
+    /// assert_eq!(
+    ///     inf_file.expand_entry(entry).unwrap(),
+    ///     InfEntry::KeyValue("Provider".to_string(), Some(InfValue::Raw("Contoso".to_string())))
+    /// );
+    /// ```
+    pub fn expand_entry(&self, entry: &InfEntry) -> Result<InfEntry, StringsError> {
+        self.string_table().expand_entry(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, &str)]) -> StringTable {
+        let mut section = InfSection::new("Strings");
+        section.extend(
+            entries.iter().map(|(k, v)| {
+                InfEntry::KeyValue(k.to_string(), Some(InfValue::Raw(v.to_string())))
+            }),
+        );
+        StringTable::from_section(&section)
+    }
+
+    #[test]
+    fn test_expand_tokens() {
+        let strings = table(&[("ProviderName", "Contoso"), ("Disk", "Disk #1")]);
+        assert_eq!(strings.expand("%providername%").unwrap(), "Contoso");
+        assert_eq!(
+            strings.expand("%Disk% from %ProviderName%").unwrap(),
+            "Disk #1 from Contoso"
+        );
+        assert_eq!(
+            strings.expand("%%SystemRoot%%\\System32").unwrap(),
+            "%SystemRoot%\\System32"
+        );
+        assert_eq!(strings.expand("%13%\\a.sys").unwrap(), "%13%\\a.sys");
+        assert_eq!(strings.expand("100%").unwrap(), "100%");
+    }
+
+    #[test]
+    fn test_string_values_as_written() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Strings]\n",
+                "Desc = Foo, Bar ; comment\n",
+                "Quoted = \"Foo, \"\"Bar\"\"\"\n",
+                "Mixed = \"Foo\", \"Bar\"\n",
+                "Long = Foo,\\\n",
+                "    Bar\n",
+            ))
+            .unwrap();
+        let strings = inf_file.string_table();
+        assert_eq!(strings.get("Desc"), Some("Foo, Bar"));
+        assert_eq!(strings.get("Quoted"), Some("Foo, \"Bar\""));
+        assert_eq!(strings.get("Mixed"), Some("\"Foo\", \"Bar\""));
+        assert_eq!(strings.get("Long"), Some("Foo,Bar"));
+        assert_eq!(string_value("\"\""), "");
+    }
+
+    #[test]
+    fn test_strings_section_lang_id() {
+        assert_eq!(strings_section_lang_id("Strings.0409"), Some(0x0409));
+        assert_eq!(strings_section_lang_id("strings.0C0A"), Some(0x0c0a));
+        assert_eq!(strings_section_lang_id("Strings"), None);
+        assert_eq!(strings_section_lang_id("Strings.NTamd64"), None);
+        assert_eq!(strings_section_lang_id("Version.0409"), None);
+        assert_eq!(strings_section_lang_id("Strings.+409"), None);
+    }
+
+    #[test]
+    fn test_localized_section_fallback() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Strings]\nName=neutral\n",
+                "[Strings.0409]\nName=en-US\n",
+                "[Strings.0809]\nName=en-GB\n",
+                "[Strings.0007]\nName=de\n",
+                "[Strings.0C07]\nName=de-AT\n",
+            ))
+            .unwrap();
+
+        let selected = |lang_id| {
+            inf_file
+                .localized_strings_section(lang_id)
+                .map(|s| s.name().to_string())
+        };
+        assert_eq!(selected(0x0809).as_deref(), Some("Strings.0809"));
+        assert_eq!(selected(0x0407).as_deref(), Some("Strings.0007"));
+        assert_eq!(selected(0x0c09).as_deref(), Some("Strings.0409"));
+        assert_eq!(selected(0x040c), None);
+        assert_eq!(
+            inf_file.string_table_for_locale(0x040c).get("Name"),
+            Some("neutral")
+        );
+    }
+
+    #[test]
+    fn test_undefined_tokens() {
+        let strings = table(&[("Known", "yes")]);
+        assert_eq!(
+            strings.expand("%Known% %Unknown%"),
+            Err(StringsError::UndefinedToken("Unknown".to_string()))
+        );
+        assert_eq!(
+            strings.undefined_tokens("%Known%,%A%,%%,%12%,%B%"),
+            vec!["A", "B"]
+        );
+    }
+}
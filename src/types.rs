@@ -1,13 +1,24 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Span;
+
 /// A value in a Windows INF file
 ///
 /// Values can be either raw strings or lists of strings.
 /// Raw strings are used for simple values, while lists are used for
 /// multi-line values or arrays.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Values compare equal when their fields are equal, whichever variant holds them, as
+/// INF text does not tell the variants apart.
+#[derive(Debug, Clone)]
 pub enum InfValue {
-    // INF values can be complex, like comma-separated lists or numbers.
-    // For simplicity, we'll treat most as strings initially.
-    // You could extend this to handle specific value types if needed.
+    /// A value made of several comma-separated fields
+    ///
+    /// Surrounding quotes and whitespace are removed from each field, so
+    /// `AudioCodec.sys = 1,,` has the fields `["1", "", ""]`. The value as it is written
+    /// is kept by the section, see [`InfSection::raw_value`].
     CommaSeparated(Vec<String>),
     /// A raw string value
     ///
@@ -19,6 +30,87 @@ pub enum InfValue {
     List(Vec<String>),
 }
 
+impl InfValue {
+    /// Builds a value from the fields of an entry
+    ///
+    /// A single field gives a raw value, more fields a comma separated one.
+    pub(crate) fn from_fields(mut fields: Vec<String>) -> Self {
+        if fields.len() == 1 {
+            InfValue::Raw(fields.remove(0))
+        } else {
+            InfValue::CommaSeparated(fields)
+        }
+    }
+
+    /// Returns the comma-separated fields of the value
+    ///
+    /// A raw value is a single field.
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            InfValue::Raw(value) => vec![value.as_str()],
+            InfValue::CommaSeparated(values) | InfValue::List(values) => {
+                values.iter().map(String::as_str).collect()
+            }
+        }
+    }
+
+    /// Returns the field at the given position, if present
+    pub fn field(&self, idx: usize) -> Option<&str> {
+        match self {
+            InfValue::Raw(value) => (idx == 0).then_some(value.as_str()),
+            InfValue::CommaSeparated(values) | InfValue::List(values) => {
+                values.get(idx).map(String::as_str)
+            }
+        }
+    }
+}
+
+impl PartialEq for InfValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields() == other.fields()
+    }
+}
+
+/// Formats the value the way it is written in an INF file
+///
+/// Fields are joined with commas, and fields that would otherwise be split or trimmed
+/// differently when read back are quoted.
+impl fmt::Display for InfValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = self.fields();
+        // a lone empty field would otherwise vanish, leaving a blank line or `key =`
+        if let [""] = fields.as_slice() {
+            return write!(f, "\"\"");
+        }
+        for (idx, field) in fields.into_iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", quote_field(field, false))?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes a field if it would otherwise be split or trimmed differently when read back,
+/// or if `force` is set
+///
+/// A field containing `=` or starting with `[` is quoted too, so that a value-only entry
+/// is not read back as a key or a section header, and so is a field with spaces or `$`,
+/// such as `"$Windows NT$"`, as INF files usually quote them.
+pub(crate) fn quote_field(field: &str, force: bool) -> Cow<'_, str> {
+    let needs_quotes = force
+        || field.contains([',', ';', '"', '=', '$'])
+        || field.contains(char::is_whitespace)
+        || field.starts_with('[')
+        || field.ends_with('\\');
+    if needs_quotes {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
 /// An entry in a Windows INF file section
 ///
 /// Entries can be either key-value pairs or standalone values.
@@ -38,14 +130,329 @@ pub enum InfEntry {
     OnlyValue(InfValue),
 }
 
+impl InfEntry {
+    /// Returns the key of a key-value entry
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            InfEntry::KeyValue(key, _) => Some(key),
+            InfEntry::OnlyValue(_) => None,
+        }
+    }
+
+    /// Returns the value of the entry, if it has one
+    pub fn value(&self) -> Option<&InfValue> {
+        match self {
+            InfEntry::KeyValue(_, value) => value.as_ref(),
+            InfEntry::OnlyValue(value) => Some(value),
+        }
+    }
+
+    /// Returns `true` if this is a key-value entry whose key matches `key`, ignoring case
+    pub fn has_key(&self, key: &str) -> bool {
+        self.key().is_some_and(|k| k.eq_ignore_ascii_case(key))
+    }
+}
+
+/// Formats the entry as a line of an INF file, `key = value` or `value`
+impl fmt::Display for InfEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InfEntry::KeyValue(key, Some(value)) => write!(f, "{key} = {value}"),
+            InfEntry::KeyValue(key, None) => write!(f, "{key} ="),
+            InfEntry::OnlyValue(value) => write!(f, "{value}"),
+        }
+    }
+}
+
 /// A section in a Windows INF file
 ///
 /// Each section in an INF file has a name and contains a list of entries.
 /// Entries can be either key-value pairs or standalone values.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Sections compare equal when their names and entries are equal; spans and the values
+/// as they are written are ignored.
+///
+/// Entries are changed through [`InfSection::push_entry`], [`InfSection::insert_entry`]
+/// and [`InfSection::remove_entry`], which keep the spans of the parsed entries in step.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct InfSection {
-    /// The name of the section
-    pub name: String,
-    /// The entries contained in this section
-    pub entries: Vec<InfEntry>,
+    name: String,
+    entries: Vec<InfEntry>,
+    span: Option<Span>,
+    /// What the parser recorded about each entry, in step with `entries`
+    sources: Vec<EntrySource>,
+}
+
+/// Where a parsed entry is and how its value is written
+#[derive(Debug, Clone, Default)]
+struct EntrySource {
+    span: Option<Span>,
+    raw_value: Option<String>,
+}
+
+impl PartialEq for InfSection {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.entries == other.entries
+    }
+}
+
+impl InfSection {
+    /// Creates an empty section without spans
+    pub fn new(name: impl Into<String>) -> Self {
+        InfSection {
+            name: name.into(),
+            ..InfSection::default()
+        }
+    }
+
+    /// Returns the name of the section
+    ///
+    /// A section in [`InfSections`] is renamed with [`InfSections::rename`], which keeps
+    /// the name lookups in step.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets where the section header is in the parsed content
+    pub(crate) fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Returns where the section header is in the parsed content
+    ///
+    /// A section repeated in the file keeps the span of its first header. Sections
+    /// that were not parsed have no span.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Returns the entries of the section, in file order
+    pub fn entries(&self) -> &[InfEntry] {
+        &self.entries
+    }
+
+    /// Returns where the entry at `idx` is in the parsed content
+    ///
+    /// Returns `None` for entries that were added after parsing.
+    pub fn entry_span(&self, idx: usize) -> Option<Span> {
+        self.sources.get(idx).and_then(|source| source.span)
+    }
+
+    /// Returns the value of the entry at `idx` as it is written in the parsed content
+    ///
+    /// The text keeps its quotes and spacing, without a trailing comment or continuation
+    /// backslashes; continued lines are joined. Returns `None` for entries that were
+    /// added after parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse_str("[Strings]\nDesc = Foo,  \"Bar\" ; comment\n").unwrap();
+    /// let strings = inf_file.section("Strings").unwrap();
+    /// assert_eq!(strings.get("Desc").unwrap().fields(), ["Foo", "Bar"]);
+    /// assert_eq!(strings.raw_value(0), Some("Foo,  \"Bar\""));
+    /// ```
+    pub fn raw_value(&self, idx: usize) -> Option<&str> {
+        self.sources
+            .get(idx)
+            .and_then(|source| source.raw_value.as_deref())
+    }
+
+    /// Returns the entries with their spans, as [`entry_span`](Self::entry_span) reports them
+    pub fn entries_with_spans(&self) -> impl Iterator<Item = (&InfEntry, Option<Span>)> {
+        self.entries
+            .iter()
+            .zip(self.sources.iter().map(|source| source.span))
+    }
+
+    /// Appends a parsed entry with its span and its value as it is written
+    pub(crate) fn push_parsed_entry(&mut self, entry: InfEntry, span: Span, raw_value: String) {
+        self.entries.push(entry);
+        self.sources.push(EntrySource {
+            span: Some(span),
+            raw_value: Some(raw_value),
+        });
+    }
+
+    /// Appends an entry, which has no span or written value
+    pub fn push_entry(&mut self, entry: InfEntry) {
+        self.entries.push(entry);
+        self.sources.push(EntrySource::default());
+    }
+
+    /// Inserts an entry at `idx`, which has no span or written value
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is greater than the number of entries.
+    pub fn insert_entry(&mut self, idx: usize, entry: InfEntry) {
+        self.entries.insert(idx, entry);
+        self.sources.insert(idx, EntrySource::default());
+    }
+
+    /// Removes and returns the entry at `idx`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn remove_entry(&mut self, idx: usize) -> InfEntry {
+        self.sources.remove(idx);
+        self.entries.remove(idx)
+    }
+
+    /// Returns the value of the first entry with the given key, ignoring case
+    ///
+    /// Returns `None` if there is no such entry or if the entry has no value.
+    pub fn get(&self, key: &str) -> Option<&InfValue> {
+        self.entries
+            .iter()
+            .find(|entry| entry.has_key(key))
+            .and_then(InfEntry::value)
+    }
+
+    /// Returns the values of all entries with the given key, ignoring case
+    ///
+    /// Keys such as `AddInterface` or `CopyFiles` may appear several times in a section.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a InfValue> + 'a {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.has_key(key))
+            .filter_map(InfEntry::value)
+    }
+}
+
+/// Appends entries, which have no span or written value
+impl Extend<InfEntry> for InfSection {
+    fn extend<I: IntoIterator<Item = InfEntry>>(&mut self, entries: I) {
+        for entry in entries {
+            self.push_entry(entry);
+        }
+    }
+}
+
+/// Formats the section as it is written in an INF file, the header followed by one line
+/// per entry
+impl fmt::Display for InfSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[{}]", self.name)?;
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The sections of a Windows INF file, in the order they appear in the file
+///
+/// Sections can be looked up by name or by their position in the file. Like Windows,
+/// name lookups ignore ASCII case, while each section keeps the spelling it was
+/// first declared with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InfSections {
+    sections: Vec<InfSection>,
+    index: HashMap<String, usize>,
+}
+
+impl InfSections {
+    /// Returns the number of sections
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Returns `true` if there are no sections
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Returns the section with the given name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&InfSection> {
+        self.index_of(name).map(|idx| &self.sections[idx])
+    }
+
+    /// Returns a mutable reference to the section with the given name, ignoring case
+    ///
+    /// Use [`InfSections::rename`] to change the name of the section.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut InfSection> {
+        self.index_of(name).map(|idx| &mut self.sections[idx])
+    }
+
+    /// Returns `true` if a section with the given name exists, ignoring case
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.index_of(name).is_some()
+    }
+
+    /// Returns the section at the given position in the file
+    pub fn get_index(&self, idx: usize) -> Option<&InfSection> {
+        self.sections.get(idx)
+    }
+
+    /// Returns the position of the section with the given name, ignoring case
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(&name.to_ascii_lowercase()).copied()
+    }
+
+    /// Inserts a section
+    ///
+    /// A new section is appended after the existing ones. If a section with the same
+    /// name (ignoring case) already exists, it is replaced in place and the old section
+    /// is returned.
+    pub fn insert(&mut self, section: InfSection) -> Option<InfSection> {
+        let key = section.name.to_ascii_lowercase();
+        match self.index.get(&key) {
+            Some(idx) => Some(std::mem::replace(&mut self.sections[*idx], section)),
+            None => {
+                self.index.insert(key, self.sections.len());
+                self.sections.push(section);
+                None
+            }
+        }
+    }
+
+    /// Renames the section `name`, ignoring case, to `new_name`
+    ///
+    /// Returns `false` and leaves the sections unchanged if there is no section `name`
+    /// or if another section is already called `new_name`.
+    pub fn rename(&mut self, name: &str, new_name: impl Into<String>) -> bool {
+        let new_name = new_name.into();
+        let Some(idx) = self.index_of(name) else {
+            return false;
+        };
+        let new_key = new_name.to_ascii_lowercase();
+        if self.index.get(&new_key).is_some_and(|other| *other != idx) {
+            return false;
+        }
+        self.index.remove(&name.to_ascii_lowercase());
+        self.index.insert(new_key, idx);
+        self.sections[idx].name = new_name;
+        true
+    }
+
+    /// Iterates over `(name, section)` pairs in file order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &InfSection)> {
+        self.sections.iter().map(|s| (s.name.as_str(), s))
+    }
+
+    /// Iterates over the section names in file order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sections.iter().map(|s| s.name.as_str())
+    }
+
+    /// Iterates over the sections in file order
+    pub fn values(&self) -> std::slice::Iter<'_, InfSection> {
+        self.sections.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a InfSections {
+    type Item = &'a InfSection;
+    type IntoIter = std::slice::Iter<'a, InfSection>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sections.iter()
+    }
 }
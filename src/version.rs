@@ -0,0 +1,313 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    DriverVer, DriverVerError, InfEntry, InfSection, StringTable, StringsError, WinInfFile,
+};
+
+/// Errors that can occur while reading the `[Version]` section
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum VersionError {
+    /// The INF file has no `[Version]` section
+    #[error("Missing [Version] section")]
+    MissingSection,
+    /// A required directive is missing from the `[Version]` section
+    #[error("Missing required directive in [Version] section: {0}")]
+    MissingDirective(&'static str),
+    /// The `ClassGuid` directive is not a valid GUID
+    #[error("Invalid ClassGuid: {0}")]
+    InvalidClassGuid(#[from] GuidError),
+    /// The `DriverVer` directive could not be parsed
+    #[error("Invalid DriverVer: {0}")]
+    InvalidDriverVer(#[from] DriverVerError),
+    /// The `PnpLockdown` directive is neither `0` nor `1`
+    #[error("Invalid PnpLockdown value: {0}")]
+    InvalidPnpLockdown(String),
+    /// A `%strkey%` token in a directive could not be expanded
+    #[error("Failed to expand string token: {0}")]
+    Strings(#[from] StringsError),
+}
+
+/// Error returned when a string is not a valid GUID
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("invalid GUID: {0}")]
+pub struct GuidError(String);
+
+/// A GUID such as `{4d36e96c-e325-11ce-bfc1-08002be10318}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Guid {
+    /// The first 8 hex digits
+    pub data1: u32,
+    /// The next 4 hex digits
+    pub data2: u16,
+    /// The next 4 hex digits
+    pub data3: u16,
+    /// The remaining 16 hex digits
+    pub data4: [u8; 8],
+}
+
+impl FromStr for Guid {
+    type Err = GuidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || GuidError(s.to_string());
+        let inner = s
+            .trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or_else(invalid)?;
+
+        let groups: Vec<&str> = inner.split('-').collect();
+        let lengths = [8, 4, 4, 4, 12];
+        if groups.len() != lengths.len()
+            || groups
+                .iter()
+                .zip(lengths)
+                .any(|(g, len)| g.len() != len || !g.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(invalid());
+        }
+
+        let tail = format!("{}{}", groups[3], groups[4]);
+        let mut data4 = [0u8; 8];
+        for (idx, byte) in data4.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&tail[idx * 2..idx * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Guid {
+            data1: u32::from_str_radix(groups[0], 16).map_err(|_| invalid())?,
+            data2: u16::from_str_radix(groups[1], 16).map_err(|_| invalid())?,
+            data3: u16::from_str_radix(groups[2], 16).map_err(|_| invalid())?,
+            data4,
+        })
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = self.data4;
+        write!(
+            f,
+            "{{{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}}}",
+            self.data1, self.data2, self.data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+        )
+    }
+}
+
+/// A `CatalogFile` directive of the `[Version]` section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogFile {
+    /// The platform decoration, e.g. `NTamd64` for `CatalogFile.NTamd64`
+    pub decoration: Option<String>,
+    /// The name of the catalog file
+    pub file_name: String,
+}
+
+/// A typed view of the `[Version]` section
+///
+/// `%strkey%` tokens in the directives are expanded using the `[Strings]` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionInfo {
+    /// The `Signature` directive, e.g. `$Windows NT$`
+    pub signature: String,
+    /// The `Class` directive, the device setup class name
+    pub class: Option<String>,
+    /// The `ClassGuid` directive, the device setup class GUID
+    pub class_guid: Option<Guid>,
+    /// The `Provider` directive
+    pub provider: String,
+    /// The `DriverVer` directive
+    pub driver_ver: DriverVer,
+    /// The `CatalogFile` directives, undecorated and per platform
+    pub catalog_files: Vec<CatalogFile>,
+    /// The `PnpLockdown` directive, `false` if it is missing
+    pub pnp_lockdown: bool,
+}
+
+impl VersionInfo {
+    /// Returns the catalog file for a platform decoration such as `NTamd64`
+    ///
+    /// Falls back to the undecorated `CatalogFile` directive when there is no
+    /// directive for the given decoration.
+    pub fn catalog_file(&self, decoration: Option<&str>) -> Option<&str> {
+        select_catalog_file(&self.catalog_files, decoration).map(|c| c.file_name.as_str())
+    }
+}
+
+impl WinInfFile {
+    /// Returns a typed view of the `[Version]` section
+    ///
+    /// `Signature`, `Provider` and `DriverVer` are required, the other directives are
+    /// optional.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::WinInfFile;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file.parse(PathBuf::from("tests/fixtures/AudioCodec.inf")).unwrap();
+    /// let version = inf_file.version_info().unwrap();
+    /// assert_eq!(version.provider, "VS_Microsoft");
+    /// assert_eq!(version.catalog_file(Some("NTamd64")), Some("AudioCodec.cat"));
+    /// ```
+    pub fn version_info(&self) -> Result<VersionInfo, VersionError> {
+        let section = self
+            .section("Version")
+            .ok_or(VersionError::MissingSection)?;
+        let strings = self.string_table();
+        let directive = |key: &'static str| -> Result<Option<String>, VersionError> {
+            section
+                .get(key)
+                .map(|value| strings.expand(&value.fields().join(",")))
+                .transpose()
+                .map_err(VersionError::from)
+        };
+        let required = |key: &'static str| -> Result<String, VersionError> {
+            directive(key)?.ok_or(VersionError::MissingDirective(key))
+        };
+
+        let pnp_lockdown = match directive("PnpLockdown")?.as_deref() {
+            None | Some("0") => false,
+            Some("1") => true,
+            Some(other) => return Err(VersionError::InvalidPnpLockdown(other.to_string())),
+        };
+
+        Ok(VersionInfo {
+            signature: required("Signature")?,
+            class: directive("Class")?,
+            class_guid: directive("ClassGuid")?
+                .map(|guid| guid.parse())
+                .transpose()?,
+            provider: required("Provider")?,
+            driver_ver: self.driver_ver()?,
+            catalog_files: catalog_files(section, &strings)?,
+            pnp_lockdown,
+        })
+    }
+}
+
+/// Returns the catalog file for a platform decoration, or the undecorated one
+pub(crate) fn select_catalog_file<'a>(
+    catalog_files: &'a [CatalogFile],
+    decoration: Option<&str>,
+) -> Option<&'a CatalogFile> {
+    let decorated = decoration.and_then(|decoration| {
+        catalog_files.iter().find(|c| {
+            c.decoration
+                .as_deref()
+                .is_some_and(|d| d.eq_ignore_ascii_case(decoration))
+        })
+    });
+    decorated.or_else(|| catalog_files.iter().find(|c| c.decoration.is_none()))
+}
+
+pub(crate) fn catalog_files(
+    section: &InfSection,
+    strings: &StringTable,
+) -> Result<Vec<CatalogFile>, VersionError> {
+    let mut catalog_files = vec![];
+    for entry in section.entries() {
+        let InfEntry::KeyValue(key, Some(value)) = entry else {
+            continue;
+        };
+        let decoration = match key.split_once('.') {
+            Some((name, decoration)) if name.eq_ignore_ascii_case("CatalogFile") => {
+                Some(decoration.to_string())
+            }
+            None if key.eq_ignore_ascii_case("CatalogFile") => None,
+            _ => continue,
+        };
+        catalog_files.push(CatalogFile {
+            decoration,
+            file_name: strings.expand(&value.fields().join(","))?,
+        });
+    }
+    Ok(catalog_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guid_parse() {
+        let guid: Guid = "{4d36e96c-e325-11ce-bfc1-08002be10318}".parse().unwrap();
+        assert_eq!(guid.data1, 0x4d36e96c);
+        assert_eq!(guid.data2, 0xe325);
+        assert_eq!(guid.data3, 0x11ce);
+        assert_eq!(guid.data4, [0xbf, 0xc1, 0x08, 0x00, 0x2b, 0xe1, 0x03, 0x18]);
+        assert_eq!(guid.to_string(), "{4d36e96c-e325-11ce-bfc1-08002be10318}");
+        assert_eq!(
+            "{4D36E96C-E325-11CE-BFC1-08002BE10318}".parse::<Guid>(),
+            Ok(guid)
+        );
+
+        assert!(
+            "4d36e96c-e325-11ce-bfc1-08002be10318"
+                .parse::<Guid>()
+                .is_err()
+        );
+        assert!(
+            "{4d36e96c-e325-11ce-bfc1-08002be1031}"
+                .parse::<Guid>()
+                .is_err()
+        );
+        assert!(
+            "{4d36e96c-e325-11ce-bfc1-08002be1031g}"
+                .parse::<Guid>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_version_info_errors() {
+        let mut inf_file = WinInfFile::default();
+        inf_file.parse_str("[Strings]\nA=a\n").unwrap();
+        assert_eq!(inf_file.version_info(), Err(VersionError::MissingSection));
+
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str("[Version]\nSignature=\"$Windows NT$\"\nDriverVer=01/02/2023,1.2.3.4\n")
+            .unwrap();
+        assert_eq!(
+            inf_file.version_info(),
+            Err(VersionError::MissingDirective("Provider"))
+        );
+
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\nSignature=\"$Windows NT$\"\nProvider=%Missing%\n",
+                "DriverVer=01/02/2023,1.2.3.4\n"
+            ))
+            .unwrap();
+        assert_eq!(
+            inf_file.version_info(),
+            Err(VersionError::Strings(StringsError::UndefinedToken(
+                "Missing".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_version_info_catalog_files() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\nSignature=\"$Windows NT$\"\nProvider=Contoso\n",
+                "DriverVer=01/02/2023,1.2.3.4\n",
+                "CatalogFile=generic.cat\nCatalogFile.NTamd64=x64.cat\n",
+                "ClassGuid={4d36e968-e325-11ce-bfc1-08002be10318}\nPnpLockdown=1\n",
+            ))
+            .unwrap();
+        let version = inf_file.version_info().unwrap();
+        assert_eq!(version.catalog_files.len(), 2);
+        assert_eq!(version.catalog_file(Some("ntamd64")), Some("x64.cat"));
+        assert_eq!(version.catalog_file(Some("NTarm64")), Some("generic.cat"));
+        assert_eq!(version.catalog_file(None), Some("generic.cat"));
+        assert!(version.class_guid.is_some());
+        assert!(version.class.is_none());
+        assert!(version.pnp_lockdown);
+    }
+}
@@ -0,0 +1,390 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::fields::{find_key_separator, quoted_fields};
+use crate::types::quote_field;
+use crate::{InfEntry, InfSection, WinInfFile, validate_section_name};
+
+/// Errors that can occur while writing an INF file
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+    /// A section name cannot be written as a section header
+    #[error("Invalid section name: {0}")]
+    InvalidSectionName(String),
+    /// A key would not be read back as the same key
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    /// A field contains a line break, which INF files cannot represent
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+    /// The output could not be written
+    #[error("Failed to write INF file: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// The encoding of a written INF file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum InfEncoding {
+    /// UTF-8 without a byte order mark
+    #[default]
+    Utf8,
+    /// UTF-16LE with a byte order mark, the encoding of most Windows driver INF files
+    Utf16Le,
+}
+
+/// Options for writing an INF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// The encoding of the output
+    pub encoding: InfEncoding,
+    /// Ends lines with `\r\n` instead of `\n`
+    pub crlf: bool,
+    /// Breaks entries longer than this many characters into continuation lines
+    ///
+    /// Lines are only broken after a comma, so a single long field is never split.
+    pub max_line_width: Option<usize>,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            encoding: InfEncoding::Utf8,
+            crlf: true,
+            max_line_width: None,
+        }
+    }
+}
+
+/// Doubles the `%` signs in literal text so that it is not read as a `%strkey%` token
+///
+/// Values of a parsed file keep their tokens and escapes as written and do not need this;
+/// it is meant for text that should appear as it is once the file is read back.
+///
+/// # Examples
+///
+/// ```
+/// use inf_rs::escape_percent;
+///
+/// assert_eq!(escape_percent("100%"), "100%%");
+/// ```
+pub fn escape_percent(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+/// Formats an entry as one or more lines, breaking after commas with a continuation `\`
+///
+/// Fields that are quoted in `raw_value`, the value as it was written, stay quoted.
+fn entry_lines(
+    entry: &InfEntry,
+    raw_value: Option<&str>,
+    max_line_width: Option<usize>,
+) -> Result<Vec<String>, WriteError> {
+    if let Some(key) = entry.key() {
+        // the key must end at our `=` and must not start a comment
+        if key.contains(['\r', '\n']) || find_key_separator(&format!("{key}=")) != Some(key.len()) {
+            return Err(WriteError::InvalidKey(key.to_string()));
+        }
+    }
+    let fields = entry
+        .value()
+        .map(|value| value.fields())
+        .unwrap_or_default();
+    if let Some(field) = fields.iter().find(|field| field.contains(['\r', '\n'])) {
+        return Err(WriteError::InvalidValue(field.to_string()));
+    }
+
+    let quoted = raw_value.map(quoted_fields).unwrap_or_default();
+    let fields: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| quote_field(field, quoted.get(idx).copied().unwrap_or(false)))
+        .collect();
+    let prefix = match entry {
+        InfEntry::KeyValue(key, Some(_)) => format!("{key} = "),
+        InfEntry::KeyValue(key, None) => return Ok(vec![format!("{key} =")]),
+        InfEntry::OnlyValue(_) => String::new(),
+    };
+    // a lone empty field would otherwise vanish, leaving a blank line or `key =`
+    if matches!(fields.as_slice(), [field] if field.is_empty()) {
+        return Ok(vec![format!("{prefix}\"\"")]);
+    }
+
+    let line = format!("{prefix}{}", fields.join(","));
+    let Some(max_line_width) = max_line_width else {
+        return Ok(vec![line]);
+    };
+    if line.chars().count() <= max_line_width || fields.len() < 2 {
+        return Ok(vec![line]);
+    }
+
+    let mut lines = vec![];
+    let mut current = prefix;
+    let mut has_field = false;
+    for field in fields {
+        if has_field {
+            // `,\` keeps the width below the limit when the next field moves down
+            if current.chars().count() + 1 + field.chars().count() + 2 > max_line_width {
+                current.push_str(",\\");
+                lines.push(std::mem::replace(&mut current, "    ".to_string()));
+            } else {
+                current.push(',');
+            }
+        }
+        current.push_str(&field);
+        has_field = true;
+    }
+    lines.push(current);
+    Ok(lines)
+}
+
+impl WinInfFile {
+    /// Returns the text of the INF file, before encoding
+    ///
+    /// Sections are written in file order and separated by a blank line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inf_rs::{WinInfFile, WriteOptions};
+    ///
+    /// let mut inf_file = WinInfFile::default();
+    /// inf_file
+    ///     .parse_str("[Version]\nSignature=\"$Windows NT$\"\n[Strings]\nName = \"A, B\"\n")
+    ///     .unwrap();
+    /// let options = WriteOptions {
+    ///     crlf: false,
+    ///     ..WriteOptions::default()
+    /// };
+    /// assert_eq!(
+    ///     inf_file.to_inf_string(&options).unwrap(),
+    ///     "[Version]\nSignature = \"$Windows NT$\"\n\n[Strings]\nName = \"A, B\"\n"
+    /// );
+    /// ```
+    pub fn to_inf_string(&self, options: &WriteOptions) -> Result<String, WriteError> {
+        let newline = if options.crlf { "\r\n" } else { "\n" };
+        let mut text = String::new();
+        for (idx, section) in self.sections.values().enumerate() {
+            if idx > 0 {
+                text.push_str(newline);
+            }
+            write_section(&mut text, section, options, newline)?;
+        }
+        Ok(text)
+    }
+
+    /// Returns the encoded bytes of the INF file
+    ///
+    /// UTF-16LE output starts with a byte order mark, which the parser uses to detect it.
+    pub fn to_bytes(&self, options: &WriteOptions) -> Result<Vec<u8>, WriteError> {
+        let text = self.to_inf_string(options)?;
+        Ok(match options.encoding {
+            InfEncoding::Utf8 => text.into_bytes(),
+            InfEncoding::Utf16Le => [0xfeff]
+                .into_iter()
+                .chain(text.encode_utf16())
+                .flat_map(u16::to_le_bytes)
+                .collect(),
+        })
+    }
+
+    /// Writes the INF file to a writer
+    pub fn write_to<W: Write>(
+        &self,
+        mut writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteError> {
+        writer.write_all(&self.to_bytes(options)?)?;
+        Ok(())
+    }
+
+    /// Writes the INF file to a path, replacing any existing file
+    pub fn save<P: AsRef<Path>>(&self, path: P, options: &WriteOptions) -> Result<(), WriteError> {
+        self.write_to(File::create(path)?, options)
+    }
+}
+
+fn write_section(
+    text: &mut String,
+    section: &InfSection,
+    options: &WriteOptions,
+    newline: &str,
+) -> Result<(), WriteError> {
+    if section.name().contains(['\r', '\n', ']'])
+        || (!section.name().starts_with('"') && section.name().contains(';'))
+        || validate_section_name(section.name().to_string()).is_err()
+    {
+        return Err(WriteError::InvalidSectionName(section.name().to_string()));
+    }
+    text.push_str(&format!("[{}]{newline}", section.name()));
+    for (idx, entry) in section.entries().iter().enumerate() {
+        for line in entry_lines(entry, section.raw_value(idx), options.max_line_width)? {
+            text.push_str(&line);
+            text.push_str(newline);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InfValue;
+
+    fn round_trip(inf_file: &WinInfFile, options: &WriteOptions) -> WinInfFile {
+        let mut read_back = WinInfFile::default();
+        read_back
+            .parse_bytes(&inf_file.to_bytes(options).unwrap())
+            .unwrap();
+        read_back
+    }
+
+    #[test]
+    fn test_write_quoting_and_escapes() {
+        let mut inf_file = WinInfFile::default();
+        let mut strings = InfSection::new("Strings");
+        strings.extend([
+            InfEntry::KeyValue(
+                "Percent".to_string(),
+                Some(InfValue::Raw(escape_percent("100%"))),
+            ),
+            InfEntry::KeyValue(
+                "Quoted".to_string(),
+                Some(InfValue::Raw("say \"hi\"; ok ".to_string())),
+            ),
+            InfEntry::KeyValue("Empty".to_string(), Some(InfValue::Raw(String::new()))),
+            InfEntry::OnlyValue(InfValue::Raw("a=b".to_string())),
+            InfEntry::OnlyValue(InfValue::Raw("[x]".to_string())),
+            InfEntry::OnlyValue(InfValue::CommaSeparated(vec![
+                "1".to_string(),
+                String::new(),
+                "trailing\\".to_string(),
+            ])),
+        ]);
+        inf_file.sections.insert(strings);
+        let options = WriteOptions {
+            crlf: false,
+            ..WriteOptions::default()
+        };
+        assert_eq!(
+            inf_file.to_inf_string(&options).unwrap(),
+            concat!(
+                "[Strings]\n",
+                "Percent = 100%%\n",
+                "Quoted = \"say \"\"hi\"\"; ok \"\n",
+                "Empty = \"\"\n",
+                "\"a=b\"\n",
+                "\"[x]\"\n",
+                "1,,\"trailing\\\"\n",
+            )
+        );
+        let read_back = round_trip(&inf_file, &options);
+        assert_eq!(read_back.sections, inf_file.sections);
+        let percent = read_back
+            .section("Strings")
+            .unwrap()
+            .get("Percent")
+            .unwrap();
+        assert_eq!(read_back.expand(percent.field(0).unwrap()).unwrap(), "100%");
+    }
+
+    #[test]
+    fn test_write_keeps_quotes() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str(concat!(
+                "[Version]\n",
+                "Signature=\"$Windows NT$\"\n",
+                "Class=\"Net\"\n",
+                "Provider=%Contoso%\n",
+                "[Strings]\n",
+                "Contoso = Contoso Ltd\n",
+            ))
+            .unwrap();
+        let mut list = InfSection::new("List");
+        list.extend([
+            InfEntry::KeyValue(
+                "Items".to_string(),
+                Some(InfValue::List(vec![
+                    "a b".to_string(),
+                    String::new(),
+                    "c".to_string(),
+                ])),
+            ),
+            InfEntry::OnlyValue(InfValue::List(vec!["single".to_string()])),
+        ]);
+        inf_file.sections.insert(list);
+        let options = WriteOptions {
+            crlf: false,
+            ..WriteOptions::default()
+        };
+        assert_eq!(
+            inf_file.to_inf_string(&options).unwrap(),
+            concat!(
+                "[Version]\n",
+                "Signature = \"$Windows NT$\"\n",
+                "Class = \"Net\"\n",
+                "Provider = %Contoso%\n",
+                "\n",
+                "[Strings]\n",
+                "Contoso = \"Contoso Ltd\"\n",
+                "\n",
+                "[List]\n",
+                "Items = \"a b\",,c\n",
+                "single\n",
+            )
+        );
+        let read_back = round_trip(&inf_file, &options);
+        assert_eq!(read_back.sections, inf_file.sections);
+        assert_eq!(
+            read_back.section("Version").unwrap().raw_value(0),
+            Some("\"$Windows NT$\"")
+        );
+    }
+
+    #[test]
+    fn test_write_continuations_and_encodings() {
+        let mut inf_file = WinInfFile::default();
+        inf_file
+            .parse_str("[Reg]\nHKR,,Blob,1,00,01,02,03,04,05,06,07,08,09,0a,0b,0c,0d,0e,0f\n")
+            .unwrap();
+        let options = WriteOptions {
+            encoding: InfEncoding::Utf16Le,
+            crlf: true,
+            max_line_width: Some(20),
+        };
+        let text = inf_file.to_inf_string(&options).unwrap();
+        assert!(text.lines().all(|line| line.chars().count() <= 20));
+        assert!(text.lines().nth(1).unwrap().ends_with(",\\"));
+
+        let bytes = inf_file.to_bytes(&options).unwrap();
+        assert_eq!(&bytes[..4], &[0xff, 0xfe, b'[', 0]);
+        assert_eq!(round_trip(&inf_file, &options).sections, inf_file.sections);
+    }
+
+    #[test]
+    fn test_write_invalid_names() {
+        let invalid = |name: &str, entries: Vec<InfEntry>| {
+            let mut section = InfSection::new(name);
+            section.extend(entries);
+            let mut inf_file = WinInfFile::default();
+            inf_file.sections.insert(section);
+            inf_file.to_inf_string(&WriteOptions::default())
+        };
+        assert!(matches!(
+            invalid("A]B", vec![]),
+            Err(WriteError::InvalidSectionName(_))
+        ));
+        assert!(matches!(
+            invalid("A", vec![InfEntry::KeyValue("k=v".to_string(), None)]),
+            Err(WriteError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            invalid(
+                "A",
+                vec![InfEntry::OnlyValue(InfValue::Raw("a\nb".to_string()))]
+            ),
+            Err(WriteError::InvalidValue(_))
+        ));
+    }
+}
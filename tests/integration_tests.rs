@@ -1,4 +1,8 @@
-use inf_rs::{InfEntry, InfValue, WinInfFile};
+use inf_rs::{
+    Arch, ComplianceLevel, DiridTable, ErrorControl, InfDocument, InfEncoding, InfEntry,
+    InfSection, InfValue, Linter, PathStyle, Platform, Position, RegistryRoot, RegistryValue,
+    ServiceType, Severity, StartType, StringsError, WinInfFile, WriteOptions,
+};
 use std::path::PathBuf;
 
 #[test]
@@ -11,54 +15,51 @@ fn test_audiocodec_inf_parsing() {
 
     // Test Version section
     let version_section = inf_file.sections.get("Version").unwrap();
-    assert_eq!(version_section.entries.len(), 7);
-    let (key, value) = read_key_value(version_section.entries[0].clone()).unwrap();
+    assert_eq!(version_section.entries().len(), 7);
+    let (key, value) = read_key_value(version_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "Signature");
     assert_eq!(value.unwrap(), InfValue::Raw("$WINDOWS NT$".to_string()));
 
-    let (key, value) = read_key_value(version_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "Class");
     assert_eq!(value.unwrap(), InfValue::Raw("MEDIA".to_string()));
 
-    let (key, value) = read_key_value(version_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "ClassGuid");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{4d36e96c-e325-11ce-bfc1-08002be10318}".to_string())
     );
 
-    let (key, value) = read_key_value(version_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "Provider");
     assert_eq!(value.unwrap(), InfValue::Raw("%ProviderName%".to_string()));
 
-    let (key, value) = read_key_value(version_section.entries[4].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[4].clone()).unwrap();
     assert_eq!(key, "DriverVer");
-    assert_eq!(
-        value.unwrap(),
-        InfValue::Raw("07/07/2021, 1.0.0.0".to_string())
-    );
+    assert_eq!(value.unwrap(), comma_separated(&["07/07/2021", "1.0.0.0"]));
 
-    let (key, value) = read_key_value(version_section.entries[5].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[5].clone()).unwrap();
     assert_eq!(key, "CatalogFile");
     assert_eq!(value.unwrap(), InfValue::Raw("AudioCodec.cat".to_string()));
 
-    let (key, value) = read_key_value(version_section.entries[6].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[6].clone()).unwrap();
     assert_eq!(key, "PnpLockDown");
     assert_eq!(value.unwrap(), InfValue::Raw("1".to_string()));
 
     // Test DestinationDirs section
     let dest_dirs_section = inf_file.sections.get("DestinationDirs").unwrap();
-    let (key, value) = read_key_value(dest_dirs_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(dest_dirs_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "DefaultDestDir");
     assert_eq!(value.unwrap(), InfValue::Raw("13".to_string()));
 
     // Test Manufacturer section
     let manufacturer_section = inf_file.sections.get("Manufacturer").unwrap();
-    let (key, value) = read_key_value(manufacturer_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(manufacturer_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "%StdMfg%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("Standard,NT$ARCH$.10.0...19041".to_string())
+        comma_separated(&["Standard", "NT$ARCH$.10.0...19041"])
     );
 
     // Test Standard.NT$ARCH$.10.0...19041 section
@@ -66,16 +67,16 @@ fn test_audiocodec_inf_parsing() {
         .sections
         .get("Standard.NT$ARCH$.10.0...19041")
         .unwrap();
-    let (key, value) = read_key_value(standard_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(standard_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "%AudioCodec.DeviceDesc%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("Audio_Device, ROOT\\AudioCodec".to_string())
+        comma_separated(&["Audio_Device", "ROOT\\AudioCodec"])
     );
 
     // Test Audio_Device.NT section
     let audio_device_nt_section = inf_file.sections.get("Audio_Device.NT").unwrap();
-    let (key, value) = read_key_value(audio_device_nt_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(audio_device_nt_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "CopyFiles");
     assert_eq!(
         value.unwrap(),
@@ -84,39 +85,44 @@ fn test_audiocodec_inf_parsing() {
 
     // Test Audio_Device.NT.Copy section
     let audio_device_nt_copy_section = inf_file.sections.get("Audio_Device.NT.Copy").unwrap();
-    assert_eq!(audio_device_nt_copy_section.entries.len(), 1);
-    let value = read_value_only(audio_device_nt_copy_section.entries[0].clone()).unwrap();
+    assert_eq!(audio_device_nt_copy_section.entries().len(), 1);
+    let value = read_value_only(audio_device_nt_copy_section.entries()[0].clone()).unwrap();
     assert_eq!(value, InfValue::Raw("AudioCodec.sys".to_string()));
 
     // Test Audio_Device.NT.Services section
     let audio_device_nt_services_section =
         inf_file.sections.get("Audio_Device.NT.Services").unwrap();
-    let (key, value) = read_key_value(audio_device_nt_services_section.entries[0].clone()).unwrap();
+    let (key, value) =
+        read_key_value(audio_device_nt_services_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "AddService");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("AudioCodec, %SPSVCINST_ASSOCSERVICE%, Audio_Service_Inst".to_string())
+        comma_separated(&[
+            "AudioCodec",
+            "%SPSVCINST_ASSOCSERVICE%",
+            "Audio_Service_Inst"
+        ])
     );
 
     // Test Audio_Service_Inst section
     let audio_service_inst_section = inf_file.sections.get("Audio_Service_Inst").unwrap();
-    assert_eq!(audio_service_inst_section.entries.len(), 5);
-    let (key, value) = read_key_value(audio_service_inst_section.entries[0].clone()).unwrap();
+    assert_eq!(audio_service_inst_section.entries().len(), 5);
+    let (key, value) = read_key_value(audio_service_inst_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "DisplayName");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("%AudioCodec.DeviceDesc%".to_string())
     );
-    let (key, value) = read_key_value(audio_service_inst_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(audio_service_inst_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "ServiceType");
     assert_eq!(value.unwrap(), InfValue::Raw("1".to_string()));
-    let (key, value) = read_key_value(audio_service_inst_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(audio_service_inst_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "StartType");
     assert_eq!(value.unwrap(), InfValue::Raw("3".to_string()));
-    let (key, value) = read_key_value(audio_service_inst_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(audio_service_inst_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "ErrorControl");
     assert_eq!(value.unwrap(), InfValue::Raw("1".to_string()));
-    let (key, value) = read_key_value(audio_service_inst_section.entries[4].clone()).unwrap();
+    let (key, value) = read_key_value(audio_service_inst_section.entries()[4].clone()).unwrap();
     assert_eq!(key, "ServiceBinary");
     assert_eq!(
         value.unwrap(),
@@ -125,220 +131,229 @@ fn test_audiocodec_inf_parsing() {
 
     // Test SourceDisksNames section
     let source_disks_names_section = inf_file.sections.get("SourceDisksNames").unwrap();
-    let (key, value) = read_key_value(source_disks_names_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(source_disks_names_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "1");
-    assert_eq!(
-        value.unwrap(),
-        InfValue::Raw("%DiskId1%,,,\"\"".to_string())
-    );
+    assert_eq!(value.unwrap(), comma_separated(&["%DiskId1%", "", "", ""]));
 
     // Test SourceDisksFiles section
     let source_disks_files_section = inf_file.sections.get("SourceDisksFiles").unwrap();
-    let (key, value) = read_key_value(source_disks_files_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(source_disks_files_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "AudioCodec.sys");
-    assert_eq!(value.unwrap(), InfValue::Raw("1,,".to_string()));
+    assert_eq!(value.unwrap(), comma_separated(&["1", "", ""]));
 
     // Test Audio_Device.NT.Wdf section
     let audio_device_nt_wdf_section = inf_file.sections.get("Audio_Device.NT.Wdf").unwrap();
-    let (key, value) = read_key_value(audio_device_nt_wdf_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(audio_device_nt_wdf_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "KmdfService");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("AudioCodec, Audio_wdfsect".to_string())
+        comma_separated(&["AudioCodec", "Audio_wdfsect"])
     );
 
     // Test Audio_wdfsect section
     let audio_wdfsect_section = inf_file.sections.get("Audio_wdfsect").unwrap();
-    let (key, value) = read_key_value(audio_wdfsect_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(audio_wdfsect_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "KmdfLibraryVersion");
     assert_eq!(value.unwrap(), InfValue::Raw("$KMDFVERSION$".to_string()));
 
     // Test Audio_Device.NT.Interfaces section
     let audio_device_nt_interfaces_section =
         inf_file.sections.get("Audio_Device.NT.Interfaces").unwrap();
-    assert_eq!(audio_device_nt_interfaces_section.entries.len(), 6);
+    assert_eq!(audio_device_nt_interfaces_section.entries().len(), 6);
 
     // Test render endpoint interfaces
     let (key, value) =
-        read_key_value(audio_device_nt_interfaces_section.entries[0].clone()).unwrap();
+        read_key_value(audio_device_nt_interfaces_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "AddInterface");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw(
-            "%KSCATEGORY_AUDIO%,    %KSNAME_Speaker%,  Audio_Device.I.Speaker".to_string()
-        )
+        comma_separated(&[
+            "%KSCATEGORY_AUDIO%",
+            "%KSNAME_Speaker%",
+            "Audio_Device.I.Speaker"
+        ])
     );
 
     let (key, value) =
-        read_key_value(audio_device_nt_interfaces_section.entries[1].clone()).unwrap();
+        read_key_value(audio_device_nt_interfaces_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "AddInterface");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw(
-            "%KSCATEGORY_RENDER%,   %KSNAME_Speaker%,  Audio_Device.I.Speaker".to_string()
-        )
+        comma_separated(&[
+            "%KSCATEGORY_RENDER%",
+            "%KSNAME_Speaker%",
+            "Audio_Device.I.Speaker"
+        ])
     );
 
     let (key, value) =
-        read_key_value(audio_device_nt_interfaces_section.entries[2].clone()).unwrap();
+        read_key_value(audio_device_nt_interfaces_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "AddInterface");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw(
-            "%KSCATEGORY_REALTIME%, %KSNAME_Speaker%,  Audio_Device.I.Speaker".to_string()
-        )
+        comma_separated(&[
+            "%KSCATEGORY_REALTIME%",
+            "%KSNAME_Speaker%",
+            "Audio_Device.I.Speaker"
+        ])
     );
 
     // Test mic capture endpoint interfaces
     let (key, value) =
-        read_key_value(audio_device_nt_interfaces_section.entries[3].clone()).unwrap();
+        read_key_value(audio_device_nt_interfaces_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "AddInterface");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw(
-            "%KSCATEGORY_AUDIO%,    %KSNAME_Microphone%, Audio_Device.I.Microphone".to_string()
-        )
+        comma_separated(&[
+            "%KSCATEGORY_AUDIO%",
+            "%KSNAME_Microphone%",
+            "Audio_Device.I.Microphone"
+        ])
     );
 
     let (key, value) =
-        read_key_value(audio_device_nt_interfaces_section.entries[4].clone()).unwrap();
+        read_key_value(audio_device_nt_interfaces_section.entries()[4].clone()).unwrap();
     assert_eq!(key, "AddInterface");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw(
-            "%KSCATEGORY_CAPTURE%,  %KSNAME_Microphone%, Audio_Device.I.Microphone".to_string()
-        )
+        comma_separated(&[
+            "%KSCATEGORY_CAPTURE%",
+            "%KSNAME_Microphone%",
+            "Audio_Device.I.Microphone"
+        ])
     );
 
     let (key, value) =
-        read_key_value(audio_device_nt_interfaces_section.entries[5].clone()).unwrap();
+        read_key_value(audio_device_nt_interfaces_section.entries()[5].clone()).unwrap();
     assert_eq!(key, "AddInterface");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw(
-            "%KSCATEGORY_REALTIME%, %KSNAME_Microphone%, Audio_Device.I.Microphone".to_string()
-        )
+        comma_separated(&[
+            "%KSCATEGORY_REALTIME%",
+            "%KSNAME_Microphone%",
+            "Audio_Device.I.Microphone"
+        ])
     );
 
     // Test Strings section
     let strings_section = inf_file.sections.get("Strings").unwrap();
-    assert!(strings_section.entries.len() >= 4);
+    assert!(strings_section.entries().len() >= 4);
 
-    let (key, value) = read_key_value(strings_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "KSNAME_Speaker");
     assert_eq!(value.unwrap(), InfValue::Raw("Speaker0".to_string()));
 
-    let (key, value) = read_key_value(strings_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "KSNAME_Microphone");
     assert_eq!(value.unwrap(), InfValue::Raw("Microphone0".to_string()));
 
-    let (key, value) = read_key_value(strings_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "SPSVCINST_ASSOCSERVICE");
     assert_eq!(value.unwrap(), InfValue::Raw("0x00000002".to_string()));
 
-    let (key, value) = read_key_value(strings_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "ProviderName");
     assert_eq!(value.unwrap(), InfValue::Raw("VS_Microsoft".to_string()));
     // Test remaining Strings section entries
-    let (key, value) = read_key_value(strings_section.entries[4].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[4].clone()).unwrap();
     assert_eq!(key, "Proxy.CLSID");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{17CCA71B-ECD7-11D0-B908-00A0C9223196}".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[5].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[5].clone()).unwrap();
     assert_eq!(key, "KSCATEGORY_AUDIO");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{6994AD04-93EF-11D0-A3CC-00A0C9223196}".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[6].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[6].clone()).unwrap();
     assert_eq!(key, "KSCATEGORY_RENDER");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{65E8773E-8F56-11D0-A3B9-00A0C9223196}".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[7].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[7].clone()).unwrap();
     assert_eq!(key, "KSCATEGORY_CAPTURE");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{65E8773D-8F56-11D0-A3B9-00A0C9223196}".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[8].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[8].clone()).unwrap();
     assert_eq!(key, "KSCATEGORY_REALTIME");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{EB115FFC-10C8-4964-831D-6DCB02E6F23F}".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[9].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[9].clone()).unwrap();
     assert_eq!(key, "KSNODETYPE_ANY");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{00000000-0000-0000-0000-000000000000}".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[10].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[10].clone()).unwrap();
     assert_eq!(key, "PKEY_AudioEndpoint_ControlPanelPageProvider");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},1".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[11].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[11].clone()).unwrap();
     assert_eq!(key, "PKEY_AudioEndpoint_Association");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},2".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[12].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[12].clone()).unwrap();
     assert_eq!(key, "PKEY_AudioEndpoint_Supports_EventDriven_Mode");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},7".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[13].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[13].clone()).unwrap();
     assert_eq!(key, "PKEY_AudioEndpoint_Default_VolumeInDb");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},9".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[14].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[14].clone()).unwrap();
     assert_eq!(key, "StdMfg");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("AudioCodec Device".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[15].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[15].clone()).unwrap();
     assert_eq!(key, "DiskId1");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("AudioCodec Installation Disk".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[16].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[16].clone()).unwrap();
     assert_eq!(key, "AudioCodec.DeviceDesc");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("AudioCodec Device".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[17].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[17].clone()).unwrap();
     assert_eq!(key, "Audio_Device.Speaker.szPname");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("AudioCodec Speaker".to_string())
     );
 
-    let (key, value) = read_key_value(strings_section.entries[18].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[18].clone()).unwrap();
     assert_eq!(key, "Audio_Device.Microphone.szPname");
     assert_eq!(
         value.unwrap(),
@@ -356,199 +371,202 @@ fn test_sampledisplay_inf_parsing() {
 
     // Test version section
     let version_section = inf_file.sections.get("Version").unwrap();
-    assert_eq!(version_section.entries.len(), 7);
-    let (key, value) = read_key_value(version_section.entries[0].clone()).unwrap();
+    assert_eq!(version_section.entries().len(), 7);
+    let (key, value) = read_key_value(version_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "Signature");
     assert_eq!(value.unwrap(), InfValue::Raw("$Windows NT$".to_string()));
 
-    let (key, value) = read_key_value(version_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "Class");
     assert_eq!(value.unwrap(), InfValue::Raw("Display".to_string()));
 
-    let (key, value) = read_key_value(version_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "ClassGUID");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("{4d36e968-e325-11ce-bfc1-08002be10318}".to_string())
     );
 
-    let (key, value) = read_key_value(version_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "Provider");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("%ProviderString%".to_string())
     );
 
-    let (key, value) = read_key_value(version_section.entries[4].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[4].clone()).unwrap();
     assert_eq!(key, "DriverVer");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("03/15/2011, 0.03.15.0011".to_string())
+        comma_separated(&["03/15/2011", "0.03.15.0011"])
     );
 
-    let (key, value) = read_key_value(version_section.entries[5].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[5].clone()).unwrap();
     assert_eq!(key, "CatalogFile");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("SampleDisplay.cat".to_string())
     );
 
-    let (key, value) = read_key_value(version_section.entries[6].clone()).unwrap();
+    let (key, value) = read_key_value(version_section.entries()[6].clone()).unwrap();
     assert_eq!(key, "PnpLockdown");
     assert_eq!(value.unwrap(), InfValue::Raw("1".to_string()));
 
     // Test DestinationDirs section
     let dest_dirs_section = inf_file.sections.get("DestinationDirs").unwrap();
-    let (key, value) = read_key_value(dest_dirs_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(dest_dirs_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "KDODSamp.Files");
     assert_eq!(value.unwrap(), InfValue::Raw("12".to_string()));
 
     // Test SourceDisksNames section
     let src_disks_names_section = inf_file.sections.get("SourceDisksNames").unwrap();
-    let (key, value) = read_key_value(src_disks_names_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(src_disks_names_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "0");
     assert_eq!(value.unwrap(), InfValue::Raw("%SampleDisk%".to_string()));
 
     // Test SourceDisksFiles section
     let src_disks_files_section = inf_file.sections.get("SourceDisksFiles").unwrap();
-    let (key, value) = read_key_value(src_disks_files_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(src_disks_files_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "SampleDisplay.sys");
     assert_eq!(value.unwrap(), InfValue::Raw("0".to_string()));
 
     // Test Manufacturer section
     let manufacturer_section = inf_file.sections.get("Manufacturer").unwrap();
-    let (key, value) = read_key_value(manufacturer_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(manufacturer_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "%ManufacturerName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("Standard,NTamd64,NTarm,NTarm64".to_string())
+        comma_separated(&["Standard", "NTamd64", "NTarm", "NTarm64"])
     );
 
     // Test Standard.NTamd64 section
     let std_ntamd64_section = inf_file.sections.get("Standard.NTamd64").unwrap();
-    assert_eq!(std_ntamd64_section.entries.len(), 4);
-    let (key, value) = read_key_value(std_ntamd64_section.entries[0].clone()).unwrap();
+    assert_eq!(std_ntamd64_section.entries().len(), 4);
+    let (key, value) = read_key_value(std_ntamd64_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, PCI\\CC_0300".to_string())
+        comma_separated(&["KDODSamp_Inst", "PCI\\CC_0300"])
     );
-    let (key, value) = read_key_value(std_ntamd64_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntamd64_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, PCI\\CC_0301".to_string())
+        comma_separated(&["KDODSamp_Inst", "PCI\\CC_0301"])
     );
-    let (key, value) = read_key_value(std_ntamd64_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntamd64_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, ACPI\\CLS_0003&SUBCLS_0000".to_string())
+        comma_separated(&["KDODSamp_Inst", "ACPI\\CLS_0003&SUBCLS_0000"])
     );
-    let (key, value) = read_key_value(std_ntamd64_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntamd64_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, ACPI\\CLS_0003&SUBCLS_0001".to_string())
+        comma_separated(&["KDODSamp_Inst", "ACPI\\CLS_0003&SUBCLS_0001"])
     );
 
     // Test Standard.NTarm section
     let std_ntarm_section = inf_file.sections.get("Standard.NTarm").unwrap();
-    assert_eq!(std_ntarm_section.entries.len(), 4);
-    let (key, value) = read_key_value(std_ntarm_section.entries[0].clone()).unwrap();
+    assert_eq!(std_ntarm_section.entries().len(), 4);
+    let (key, value) = read_key_value(std_ntarm_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, PCI\\CC_0300".to_string())
+        comma_separated(&["KDODSamp_Inst", "PCI\\CC_0300"])
     );
-    let (key, value) = read_key_value(std_ntarm_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntarm_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, PCI\\CC_0301".to_string())
+        comma_separated(&["KDODSamp_Inst", "PCI\\CC_0301"])
     );
-    let (key, value) = read_key_value(std_ntarm_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntarm_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, ACPI\\CLS_0003&SUBCLS_0000".to_string())
+        comma_separated(&["KDODSamp_Inst", "ACPI\\CLS_0003&SUBCLS_0000"])
     );
-    let (key, value) = read_key_value(std_ntarm_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntarm_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, ACPI\\CLS_0003&SUBCLS_0001".to_string())
+        comma_separated(&["KDODSamp_Inst", "ACPI\\CLS_0003&SUBCLS_0001"])
     );
 
     // Test Standard.NTarm64 section
     let std_ntarm64_section = inf_file.sections.get("Standard.NTarm64").unwrap();
-    assert_eq!(std_ntarm64_section.entries.len(), 4);
-    let (key, value) = read_key_value(std_ntarm64_section.entries[0].clone()).unwrap();
+    assert_eq!(std_ntarm64_section.entries().len(), 4);
+    let (key, value) = read_key_value(std_ntarm64_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, PCI\\CC_0300".to_string())
+        comma_separated(&["KDODSamp_Inst", "PCI\\CC_0300"])
     );
-    let (key, value) = read_key_value(std_ntarm64_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntarm64_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, PCI\\CC_0301".to_string())
+        comma_separated(&["KDODSamp_Inst", "PCI\\CC_0301"])
     );
-    let (key, value) = read_key_value(std_ntarm64_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntarm64_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, ACPI\\CLS_0003&SUBCLS_0000".to_string())
+        comma_separated(&["KDODSamp_Inst", "ACPI\\CLS_0003&SUBCLS_0000"])
     );
-    let (key, value) = read_key_value(std_ntarm64_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(std_ntarm64_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "%SampleDeviceName%");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw("KDODSamp_Inst, ACPI\\CLS_0003&SUBCLS_0001".to_string())
+        comma_separated(&["KDODSamp_Inst", "ACPI\\CLS_0003&SUBCLS_0001"])
     );
 
     // Test KDODSamp_Inst section
     let kdodsamp_inst_section = inf_file.sections.get("KDODSamp_Inst").unwrap();
-    let (key, value) = read_key_value(kdodsamp_inst_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_inst_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "FeatureScore");
     assert_eq!(value.unwrap(), InfValue::Raw("F9".to_string()));
-    let (key, value) = read_key_value(kdodsamp_inst_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_inst_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "CopyFiles");
     assert_eq!(value.unwrap(), InfValue::Raw("KDODSamp.Files".to_string()));
 
     // Test KDODSamp_Inst.Services section
     let kdodsamp_inst_services_section = inf_file.sections.get("KDODSamp_Inst.Services").unwrap();
-    let (key, value) = read_key_value(kdodsamp_inst_services_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_inst_services_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "AddService");
     assert_eq!(
         value.unwrap(),
-        InfValue::Raw(
-            "KDODSamp,0x00000002,KDODSamp_Service_Inst,KDODSamp_EventLog_Inst".to_string()
-        )
+        comma_separated(&[
+            "KDODSamp",
+            "0x00000002",
+            "KDODSamp_Service_Inst",
+            "KDODSamp_EventLog_Inst"
+        ])
     );
 
     // Test KDODSamp_Service_Inst section
     let kdodsamp_service_inst_section = inf_file.sections.get("KDODSamp_Service_Inst").unwrap();
-    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "ServiceType");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("%SERVICE_KERNEL_DRIVER%".to_string())
     );
-    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "StartType");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("%SERVICE_DEMAND_START%".to_string())
     );
-    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "ErrorControl");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("%SERVICE_ERROR_IGNORE%".to_string())
     );
-    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_service_inst_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "ServiceBinary");
     assert_eq!(
         value.unwrap(),
@@ -557,12 +575,12 @@ fn test_sampledisplay_inf_parsing() {
 
     // Test KDODSamp.Files section
     let kdodsamp_files_section = inf_file.sections.get("KDODSamp.Files").unwrap();
-    let value = read_value_only(kdodsamp_files_section.entries[0].clone()).unwrap();
+    let value = read_value_only(kdodsamp_files_section.entries()[0].clone()).unwrap();
     assert_eq!(value, InfValue::Raw("SampleDisplay.sys".to_string()));
 
     // Test KDODSamp_EventLog_Inst section
     let kdodsamp_eventlog_inst_section = inf_file.sections.get("KDODSamp_EventLog_Inst").unwrap();
-    let (key, value) = read_key_value(kdodsamp_eventlog_inst_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(kdodsamp_eventlog_inst_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "AddReg");
     assert_eq!(
         value.unwrap(),
@@ -574,84 +592,89 @@ fn test_sampledisplay_inf_parsing() {
         .sections
         .get("KDODSamp_EventLog_Inst.AddReg")
         .unwrap();
-    let value = read_value_only(kdodsamp_eventlog_inst_addreg_section.entries[0].clone()).unwrap();
+    let value =
+        read_value_only(kdodsamp_eventlog_inst_addreg_section.entries()[0].clone()).unwrap();
     assert_eq!(
         value,
-        InfValue::Raw(
-            "HKR,,EventMessageFile,%REG_EXPAND_SZ%,\"%%SystemRoot%%\\System32\\IoLogMsg.dll\""
-                .to_string()
-        )
+        comma_separated(&[
+            "HKR",
+            "",
+            "EventMessageFile",
+            "%REG_EXPAND_SZ%",
+            "%%SystemRoot%%\\System32\\IoLogMsg.dll"
+        ])
     );
-    let value = read_value_only(kdodsamp_eventlog_inst_addreg_section.entries[1].clone()).unwrap();
+    let value =
+        read_value_only(kdodsamp_eventlog_inst_addreg_section.entries()[1].clone()).unwrap();
     assert_eq!(
         value,
-        InfValue::Raw("HKR,,TypesSupported,%REG_DWORD%,7".to_string())
+        comma_separated(&["HKR", "", "TypesSupported", "%REG_DWORD%", "7"])
     );
 
     // Test Strings section
     let strings_section = inf_file.sections.get("Strings").unwrap();
     // There are 20 entries in the [Strings] section (7 localizable, 13 non-localizable)
-    assert_eq!(strings_section.entries.len(), 17);
+    assert_eq!(strings_section.entries().len(), 17);
 
     // Check a few string values
-    let (key, value) = read_key_value(strings_section.entries[0].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[0].clone()).unwrap();
     assert_eq!(key, "ProviderString");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("TODO-Set-Provider".to_string())
     );
-    let (key, value) = read_key_value(strings_section.entries[1].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[1].clone()).unwrap();
     assert_eq!(key, "ManufacturerName");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("TODO-Set-Manufacturer".to_string())
     );
-    let (key, value) = read_key_value(strings_section.entries[2].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[2].clone()).unwrap();
     assert_eq!(key, "SampleDisk");
     assert_eq!(value.unwrap(), InfValue::Raw("Sample Disk".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[3].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[3].clone()).unwrap();
     assert_eq!(key, "SampleDeviceName");
     assert_eq!(
         value.unwrap(),
         InfValue::Raw("Kernel mode display only sample driver".to_string())
     );
-    let (key, value) = read_key_value(strings_section.entries[4].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[4].clone()).unwrap();
     assert_eq!(key, "SERVICE_BOOT_START");
     assert_eq!(value.unwrap(), InfValue::Raw("0x0".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[5].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[5].clone()).unwrap();
     assert_eq!(key, "SERVICE_SYSTEM_START");
     assert_eq!(value.unwrap(), InfValue::Raw("0x1".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[6].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[6].clone()).unwrap();
     assert_eq!(key, "SERVICE_AUTO_START");
     assert_eq!(value.unwrap(), InfValue::Raw("0x2".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[7].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[7].clone()).unwrap();
     assert_eq!(key, "SERVICE_DEMAND_START");
     assert_eq!(value.unwrap(), InfValue::Raw("0x3".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[8].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[8].clone()).unwrap();
     assert_eq!(key, "SERVICE_DISABLED");
     assert_eq!(value.unwrap(), InfValue::Raw("0x4".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[9].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[9].clone()).unwrap();
     assert_eq!(key, "SERVICE_KERNEL_DRIVER");
     assert_eq!(value.unwrap(), InfValue::Raw("0x1".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[10].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[10].clone()).unwrap();
     assert_eq!(key, "SERVICE_ERROR_IGNORE");
     assert_eq!(value.unwrap(), InfValue::Raw("0x0".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[11].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[11].clone()).unwrap();
     assert_eq!(key, "SERVICE_ERROR_NORMAL");
     assert_eq!(value.unwrap(), InfValue::Raw("0x1".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[12].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[12].clone()).unwrap();
     assert_eq!(key, "SERVICE_ERROR_SEVERE");
     assert_eq!(value.unwrap(), InfValue::Raw("0x2".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[13].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[13].clone()).unwrap();
     assert_eq!(key, "SERVICE_ERROR_CRITICAL");
     assert_eq!(value.unwrap(), InfValue::Raw("0x3".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[14].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[14].clone()).unwrap();
     assert_eq!(key, "REG_MULTI_SZ");
     assert_eq!(value.unwrap(), InfValue::Raw("0x00010000".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[15].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[15].clone()).unwrap();
     assert_eq!(key, "REG_EXPAND_SZ");
     assert_eq!(value.unwrap(), InfValue::Raw("0x00020000".to_string()));
-    let (key, value) = read_key_value(strings_section.entries[16].clone()).unwrap();
+    let (key, value) = read_key_value(strings_section.entries()[16].clone()).unwrap();
     assert_eq!(key, "REG_DWORD");
     assert_eq!(value.unwrap(), InfValue::Raw("0x00010001".to_string()));
 }
@@ -676,6 +699,960 @@ fn test_inf_file_non_existent_section() {
     assert!(invalid_section.is_none());
 }
 
+#[test]
+fn test_parse_bytes_matches_parse() {
+    let inf_path = PathBuf::from("tests/fixtures/AudioCodec.inf");
+    let mut from_path = WinInfFile::default();
+    assert!(from_path.parse(inf_path.clone()).is_ok());
+
+    let mut from_bytes = WinInfFile::default();
+    let bytes = std::fs::read(inf_path).unwrap();
+    assert!(from_bytes.parse_bytes(&bytes).is_ok());
+
+    assert_eq!(from_path.sections.len(), from_bytes.sections.len());
+    for (section_name, section) in from_path.sections.iter() {
+        assert_eq!(from_bytes.sections.get(section_name), Some(section));
+    }
+}
+
+#[test]
+fn test_parse_reader_and_str() {
+    let mut from_reader = WinInfFile::default();
+    let file = std::fs::File::open("tests/fixtures/sampledisplay.inf").unwrap();
+    assert!(from_reader.parse_reader(file).is_ok());
+    let strings_section = from_reader.sections.get("Strings").unwrap();
+    assert_eq!(strings_section.entries().len(), 17);
+
+    let mut from_str = WinInfFile::default();
+    assert!(
+        from_str
+            .parse_str("[Version]\r\nSignature=\"$Windows NT$\"\r\nClass=Display")
+            .is_ok()
+    );
+    let version_section = from_str.sections.get("Version").unwrap();
+    let (key, value) = read_key_value(version_section.entries()[1].clone()).unwrap();
+    assert_eq!(key, "Class");
+    assert_eq!(value.unwrap(), InfValue::Raw("Display".to_string()));
+}
+
+#[test]
+fn test_sections_preserve_file_order() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+
+    let names: Vec<&str> = inf_file.sections.names().take(6).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Version",
+            "DestinationDirs",
+            "Manufacturer",
+            "Standard.NT$ARCH$.10.0...19041",
+            "Audio_Device.NT",
+            "Audio_Device.NT.Copy",
+        ]
+    );
+    assert_eq!(inf_file.sections.index_of("Manufacturer"), Some(2));
+    assert_eq!(
+        inf_file
+            .sections
+            .get_index(inf_file.sections.len() - 1)
+            .unwrap()
+            .name(),
+        "Strings"
+    );
+}
+
+#[test]
+fn test_rename_section() {
+    let mut inf_file = WinInfFile::default();
+    inf_file
+        .parse_str("[Version]\nSignature = x\n[Inst]\nCopyFiles = Files\n[Files]\na.sys\n")
+        .unwrap();
+    let sections = &mut inf_file.sections;
+
+    assert!(sections.rename("inst", "Inst.NT"));
+    assert!(!sections.contains_key("Inst"));
+    assert_eq!(sections.index_of("INST.nt"), Some(1));
+    assert_eq!(sections.get("inst.nt").unwrap().entries().len(), 1);
+    assert!(sections.rename("Inst.NT", "INST.NT"));
+    assert_eq!(sections.get("inst.nt").unwrap().name(), "INST.NT");
+
+    // the name of another section or of a missing section is not taken
+    assert!(!sections.rename("Files", "Version"));
+    assert!(!sections.rename("Missing", "Other"));
+    assert_eq!(
+        sections.names().collect::<Vec<_>>(),
+        ["Version", "INST.NT", "Files"]
+    );
+
+    assert!(sections.insert(InfSection::new("Inst")).is_none());
+    assert_eq!(sections.index_of("Inst"), Some(3));
+}
+
+#[test]
+fn test_case_insensitive_lookup() {
+    for fixture in [
+        "tests/fixtures/AudioCodec.inf",
+        "tests/fixtures/sampledisplay.inf",
+    ] {
+        let mut inf_file = WinInfFile::default();
+        assert!(inf_file.parse(PathBuf::from(fixture)).is_ok());
+
+        let version_section = inf_file.section("version").unwrap();
+        assert_eq!(version_section.name(), "Version");
+        assert_eq!(
+            version_section.get("PnpLockdown"),
+            Some(&InfValue::Raw("1".to_string()))
+        );
+        assert!(version_section.get("classguid").is_some());
+        assert!(version_section.get("NoSuchKey").is_none());
+    }
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let interfaces = inf_file.section("audio_device.nt.interfaces").unwrap();
+    assert_eq!(interfaces.get_all("addinterface").count(), 6);
+}
+
+#[test]
+fn test_case_insensitive_duplicate_sections_merged() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse_str("[Strings]\nA=1\n[strings]\nB=2\n")
+            .is_ok()
+    );
+    assert_eq!(inf_file.sections.len(), 1);
+    let strings_section = inf_file.section("STRINGS").unwrap();
+    assert_eq!(strings_section.name(), "Strings");
+    assert_eq!(strings_section.entries().len(), 2);
+    assert_eq!(inf_file.diagnostics().len(), 1);
+}
+
+#[test]
+fn test_comma_separated_fields() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse_str(concat!(
+                "[Registry.AddReg]\n",
+                "HKR,,\"Data, with comma\",%REG_BINARY%,01,02,\\\n",
+                "  03,04 ; trailing comment\n",
+                "[Strings]\n",
+                "Quoted = \"{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},1\"\n",
+                "Spaced = 1,  \"two\" ,, ; comment\n",
+            ))
+            .is_ok()
+    );
+
+    let addreg_section = inf_file.section("Registry.AddReg").unwrap();
+    assert_eq!(addreg_section.entries().len(), 1);
+    let value = read_value_only(addreg_section.entries()[0].clone()).unwrap();
+    assert_eq!(
+        value.fields(),
+        vec![
+            "HKR",
+            "",
+            "Data, with comma",
+            "%REG_BINARY%",
+            "01",
+            "02",
+            "03",
+            "04"
+        ]
+    );
+    assert_eq!(
+        value.to_string(),
+        "HKR,,\"Data, with comma\",%REG_BINARY%,01,02,03,04"
+    );
+    assert_eq!(
+        addreg_section.raw_value(0),
+        Some("HKR,,\"Data, with comma\",%REG_BINARY%,01,02,03,04")
+    );
+
+    let quoted = inf_file.section("Strings").unwrap().get("Quoted").unwrap();
+    assert_eq!(
+        quoted.field(0),
+        Some("{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},1")
+    );
+    assert_eq!(
+        quoted.to_string(),
+        "\"{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},1\""
+    );
+
+    let strings = inf_file.section("Strings").unwrap();
+    assert_eq!(strings.raw_value(1), Some("1,  \"two\" ,,"));
+    assert_eq!(strings.get("Spaced").unwrap().to_string(), "1,two,,");
+
+    // entries added in code have no written value
+    let mut strings = strings.clone();
+    strings.insert_entry(0, InfEntry::KeyValue("New".to_string(), None));
+    assert_eq!(strings.raw_value(0), None);
+    assert_eq!(strings.raw_value(2), Some("1,  \"two\" ,,"));
+}
+
+#[test]
+fn test_string_token_expansion() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+
+    let models_section = inf_file.section("Standard.NT$ARCH$.10.0...19041").unwrap();
+    let (key, value) =
+        read_key_value(inf_file.expand_entry(&models_section.entries()[0]).unwrap()).unwrap();
+    assert_eq!(key, "AudioCodec Device");
+    assert_eq!(
+        value.unwrap(),
+        comma_separated(&["Audio_Device", "ROOT\\AudioCodec"])
+    );
+
+    let services_section = inf_file.section("Audio_Device.NT.Services").unwrap();
+    let (_, value) = read_key_value(
+        inf_file
+            .expand_entry(&services_section.entries()[0])
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        value.unwrap(),
+        comma_separated(&["AudioCodec", "0x00000002", "Audio_Service_Inst"])
+    );
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let addreg_section = inf_file.section("KDODSamp_EventLog_Inst.AddReg").unwrap();
+    let value = read_value_only(inf_file.expand_entry(&addreg_section.entries()[0]).unwrap());
+    assert_eq!(
+        value.unwrap().field(4),
+        Some("%SystemRoot%\\System32\\IoLogMsg.dll")
+    );
+    assert_eq!(
+        inf_file.expand("%ProviderString% %UndefinedToken%"),
+        Err(StringsError::UndefinedToken("UndefinedToken".to_string()))
+    );
+}
+
+#[test]
+fn test_version_info() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let version = inf_file.version_info().unwrap();
+    assert_eq!(version.signature, "$Windows NT$");
+    assert_eq!(version.class.as_deref(), Some("Display"));
+    assert_eq!(
+        version.class_guid.unwrap().to_string(),
+        "{4d36e968-e325-11ce-bfc1-08002be10318}"
+    );
+    assert_eq!(version.provider, "TODO-Set-Provider");
+    assert_eq!(version.driver_ver.date.year, 2011);
+    assert_eq!(version.driver_ver.date.month, 3);
+    assert_eq!(version.driver_ver.date.day, 15);
+    assert_eq!(version.driver_ver.version.parts, [0, 3, 15, 11]);
+    assert_eq!(version.catalog_file(None), Some("SampleDisplay.cat"));
+    assert!(version.pnp_lockdown);
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let version = inf_file.version_info().unwrap();
+    assert_eq!(version.class.as_deref(), Some("MEDIA"));
+    assert_eq!(version.driver_ver.to_string(), "07/07/2021,1.0.0.0");
+    assert!(version.pnp_lockdown);
+}
+
+#[test]
+fn test_models_sections_for_platform() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+
+    let manufacturers = inf_file.manufacturers().unwrap();
+    assert_eq!(manufacturers.len(), 1);
+    assert_eq!(manufacturers[0].name, "AudioCodec Device");
+    let target = &manufacturers[0].target_os_versions[0];
+    assert_eq!(target.arch, None);
+    assert_eq!(target.build_number, Some(19041));
+
+    let win10_2004 = Platform {
+        build_number: 19041,
+        ..Platform::new(Arch::Arm64, 10, 0)
+    };
+    let models = inf_file.models_sections(&win10_2004).unwrap();
+    assert_eq!(models.len(), 1);
+    assert_eq!(models[0].section.name(), "Standard.NT$ARCH$.10.0...19041");
+
+    // too old for the only decorated section, and there is no undecorated one
+    let win10_1909 = Platform {
+        build_number: 18363,
+        ..Platform::new(Arch::Amd64, 10, 0)
+    };
+    assert!(inf_file.models_sections(&win10_1909).unwrap().is_empty());
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    for (arch, section_name) in [
+        (Arch::Amd64, "Standard.NTamd64"),
+        (Arch::Arm, "Standard.NTarm"),
+        (Arch::Arm64, "Standard.NTarm64"),
+    ] {
+        let models = inf_file
+            .models_sections(&Platform::new(arch, 10, 0))
+            .unwrap();
+        assert_eq!(models[0].section.name(), section_name);
+        assert_eq!(models[0].section.entries().len(), 4);
+    }
+    assert!(
+        inf_file
+            .models_sections(&Platform::new(Arch::X86, 10, 0))
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_find_device_by_hardware_id() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let platform = Platform {
+        build_number: 22621,
+        ..Platform::new(Arch::Amd64, 10, 0)
+    };
+    let matches = inf_file.find_device("ROOT\\AudioCodec", &platform).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].manufacturer, "AudioCodec Device");
+    assert_eq!(matches[0].model.device_description, "AudioCodec Device");
+    assert_eq!(matches[0].model.install_section, "Audio_Device");
+    assert!(!matches[0].is_compatible_id);
+    assert_eq!(
+        matches[0].install_section.unwrap().name(),
+        "Audio_Device.NT"
+    );
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let platform = Platform::new(Arch::Arm64, 10, 0);
+    let matches = inf_file
+        .find_device("ACPI\\CLS_0003&SUBCLS_0001", &platform)
+        .unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].models_section.name(), "Standard.NTarm64");
+    assert_eq!(
+        matches[0].model.device_description,
+        "Kernel mode display only sample driver"
+    );
+    assert_eq!(matches[0].install_section.unwrap().name(), "KDODSamp_Inst");
+    assert!(
+        inf_file
+            .find_device("PCI\\CC_0302", &platform)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(inf_file.model_entries(&platform).unwrap().len(), 4);
+}
+
+#[test]
+fn test_ddinstall_companion_sections() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let install = inf_file
+        .ddinstall("Audio_Device", &Platform::new(Arch::Amd64, 10, 0))
+        .unwrap();
+    assert_eq!(install.section.name(), "Audio_Device.NT");
+    assert_eq!(install.services.unwrap().name(), "Audio_Device.NT.Services");
+    assert_eq!(
+        install.interfaces.unwrap().name(),
+        "Audio_Device.NT.Interfaces"
+    );
+    assert_eq!(install.wdf.unwrap().name(), "Audio_Device.NT.Wdf");
+    assert!(install.hw.is_none());
+    assert!(install.coinstallers.is_none());
+    assert!(install.components.is_none());
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let install = inf_file
+        .ddinstall("KDODSamp_Inst", &Platform::new(Arch::Arm64, 10, 0))
+        .unwrap();
+    assert_eq!(install.decoration, None);
+    assert_eq!(install.section.name(), "KDODSamp_Inst");
+    assert_eq!(install.services.unwrap().name(), "KDODSamp_Inst.Services");
+}
+
+#[test]
+fn test_add_reg_sections() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let entries = inf_file.add_reg("Audio_Device.I.Speaker.AddReg").unwrap();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[0].root, RegistryRoot::Relative);
+    assert_eq!(entries[0].subkey, "");
+    assert_eq!(entries[0].value_name.as_deref(), Some("CLSID"));
+    assert_eq!(
+        entries[0].value,
+        Some(RegistryValue::String(
+            "{17CCA71B-ECD7-11D0-B908-00A0C9223196}".to_string()
+        ))
+    );
+    assert_eq!(entries[2].subkey, "EP\\0");
+    assert_eq!(
+        entries[2].value_name.as_deref(),
+        Some("{1DA5D803-D492-4EDD-8C23-E0C0FFEE7F0E},2")
+    );
+    assert_eq!(entries[3].value, Some(RegistryValue::Dword(1)));
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let entries = inf_file.add_reg("KDODSamp_EventLog_Inst.AddReg").unwrap();
+    assert_eq!(
+        entries[0].value,
+        Some(RegistryValue::ExpandString(
+            "%SystemRoot%\\System32\\IoLogMsg.dll".to_string()
+        ))
+    );
+    assert_eq!(entries[1].value, Some(RegistryValue::Dword(7)));
+}
+
+#[test]
+fn test_add_services() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let services = inf_file.add_services("Audio_Device.NT.Services").unwrap();
+    assert_eq!(services.len(), 1);
+    assert_eq!(
+        services[0].install_section.as_deref(),
+        Some("Audio_Service_Inst")
+    );
+    assert_eq!(services[0].service_type, Some(ServiceType::KernelDriver));
+    assert_eq!(services[0].error_control, Some(ErrorControl::Normal));
+    assert_eq!(
+        services[0].service_binary.as_deref(),
+        Some("%13%\\AudioCodec.sys")
+    );
+    assert!(services[0].event_log.is_none());
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let services = inf_file.add_services("KDODSamp_Inst.Services").unwrap();
+    assert_eq!(services[0].name, "KDODSamp");
+    assert_eq!(services[0].start_type, Some(StartType::Demand));
+    assert_eq!(services[0].error_control, Some(ErrorControl::Ignore));
+    let event_log = services[0].event_log.as_ref().unwrap();
+    assert_eq!(event_log.section, "KDODSamp_EventLog_Inst");
+    assert_eq!(event_log.log_type, "System");
+    assert_eq!(event_log.event_name, "KDODSamp");
+    assert_eq!(event_log.add_reg.len(), 2);
+    assert_eq!(event_log.add_reg[1].value, Some(RegistryValue::Dword(7)));
+}
+
+#[test]
+fn test_copy_files() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let files = inf_file.copy_files("Audio_Device.NT").unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].section.as_deref(), Some("Audio_Device.NT.Copy"));
+    assert_eq!(files[0].source_file, "AudioCodec.sys");
+    assert_eq!(files[0].destination_file, "AudioCodec.sys");
+    assert_eq!(files[0].destination.dirid, 13);
+    assert_eq!(files[0].destination.subdir, None);
+    assert_eq!(files[0].flags, 0);
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let files = inf_file.copy_files("KDODSamp_Inst").unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].destination.to_string(), "%12%");
+    assert!(inf_file.del_files("KDODSamp_Inst").unwrap().is_empty());
+}
+
+#[test]
+fn test_render_destination_paths() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let dirids = DiridTable::new("C:\\");
+    let files = inf_file.copy_files("KDODSamp_Inst").unwrap();
+    assert_eq!(
+        dirids
+            .render_destination(&files[0].destination, PathStyle::Symbolic)
+            .unwrap(),
+        "<Drivers>"
+    );
+    let services = inf_file.add_services("KDODSamp_Inst.Services").unwrap();
+    let binary = services[0].service_binary.as_deref().unwrap();
+    assert_eq!(
+        dirids.render(binary, PathStyle::Concrete).unwrap(),
+        "C:\\Windows\\System32\\drivers\\SampleDisplay.sys"
+    );
+}
+
+#[test]
+fn test_source_manifest() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+            .is_ok()
+    );
+    let manifest = inf_file.source_manifest(Some(Arch::Amd64)).unwrap();
+    assert_eq!(manifest.disks.len(), 1);
+    assert_eq!(manifest.disks[0].path, None);
+    assert_eq!(manifest.files.len(), 1);
+    assert_eq!(manifest.files[0].name, "AudioCodec.sys");
+    assert_eq!(manifest.files[0].disk_id, 1);
+    assert_eq!(manifest.files[0].subdir, None);
+    assert_eq!(manifest.files[0].size, None);
+
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let manifest = inf_file.source_manifest(None).unwrap();
+    assert_eq!(manifest.disk(0).unwrap().description, "Sample Disk");
+    assert_eq!(
+        manifest.source_path("SampleDisplay.sys").unwrap(),
+        "SampleDisplay.sys"
+    );
+}
+
+#[test]
+fn test_check_package() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+            .is_ok()
+    );
+    let dir = std::env::temp_dir().join(format!("inf-rs-package-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(
+        "tests/fixtures/sampledisplay.inf",
+        dir.join("sampledisplay.inf"),
+    )
+    .unwrap();
+    std::fs::write(dir.join("sampledisplay.sys"), b"").unwrap();
+    std::fs::write(dir.join("sampledisplay.pdb"), b"").unwrap();
+
+    let report = inf_file.check_package(&dir, Some(Arch::Amd64)).unwrap();
+    assert!(report.missing_files.is_empty());
+    assert!(report.unlisted_files.is_empty());
+    assert_eq!(report.unreferenced_files, vec!["sampledisplay.pdb"]);
+    assert_eq!(report.missing_catalogs, vec!["SampleDisplay.cat"]);
+
+    std::fs::write(dir.join("SampleDisplay.cat"), b"").unwrap();
+    std::fs::remove_file(dir.join("sampledisplay.pdb")).unwrap();
+    let report = inf_file.check_package(&dir, Some(Arch::Amd64)).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(report.is_complete());
+}
+
+#[test]
+fn test_check_package_two_arch() {
+    let mut inf_file = WinInfFile::default();
+    assert!(
+        inf_file
+            .parse(PathBuf::from("tests/fixtures/TwoArch.inf"))
+            .is_ok()
+    );
+    let dir = std::env::temp_dir().join(format!("inf-rs-two-arch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for file in [
+        "TwoArch.inf",
+        "TwoArch.cat",
+        "TwoArch_x64.sys",
+        "TwoArch.dll",
+    ] {
+        std::fs::write(dir.join(file), b"").unwrap();
+    }
+
+    // the arm64 install sections are not walked for amd64
+    let report = inf_file.check_package(&dir, Some(Arch::Amd64)).unwrap();
+    assert!(report.is_complete(), "{report:?}");
+
+    let report = inf_file.check_package(&dir, Some(Arch::Arm64)).unwrap();
+    assert_eq!(
+        report.missing_files,
+        vec!["TwoArch_arm64.sys", "TwoArch_arm64_extra.dll"]
+    );
+    assert_eq!(report.unlisted_files, vec!["TwoArch_arm64_extra.dll"]);
+    assert_eq!(report.unreferenced_files, vec!["TwoArch_x64.sys"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_round_trip() {
+    for fixture in [
+        "tests/fixtures/AudioCodec.inf",
+        "tests/fixtures/sampledisplay.inf",
+    ] {
+        let mut inf_file = WinInfFile::default();
+        assert!(inf_file.parse(PathBuf::from(fixture)).is_ok());
+        for encoding in [InfEncoding::Utf8, InfEncoding::Utf16Le] {
+            let options = WriteOptions {
+                encoding,
+                max_line_width: Some(40),
+                ..WriteOptions::default()
+            };
+            let bytes = inf_file.to_bytes(&options).unwrap();
+            let mut read_back = WinInfFile::default();
+            assert!(read_back.parse_bytes(&bytes).is_ok());
+            assert_eq!(read_back.sections, inf_file.sections);
+        }
+    }
+}
+
+#[test]
+fn test_document_lossless_round_trip() {
+    for fixture in [
+        "tests/fixtures/AudioCodec.inf",
+        "tests/fixtures/sampledisplay.inf",
+    ] {
+        let bytes = std::fs::read(fixture).unwrap();
+        let document = InfDocument::parse(&bytes).unwrap();
+        assert_eq!(document.to_bytes(), bytes);
+
+        let mut inf_file = WinInfFile::default();
+        assert!(inf_file.parse_bytes(&bytes).is_ok());
+        assert_eq!(document.to_inf_file().unwrap().sections, inf_file.sections);
+    }
+
+    let bytes = std::fs::read("tests/fixtures/AudioCodec.inf").unwrap();
+    let mut document = InfDocument::parse(&bytes).unwrap();
+    document
+        .set_value("Version", "DriverVer", "01/02/2025,2.0.0.0")
+        .unwrap();
+    let edited = document.to_bytes();
+    assert_eq!(&edited[..2], &[0xff, 0xfe]);
+    let inf_file = document.to_inf_file().unwrap();
+    assert_eq!(
+        inf_file.driver_ver().unwrap().to_string(),
+        "01/02/2025,2.0.0.0"
+    );
+    assert_eq!(
+        document.to_string().lines().count(),
+        String::from_utf16_lossy(
+            &bytes[2..]
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>()
+        )
+        .lines()
+        .count()
+    );
+}
+
+#[test]
+fn test_spans() {
+    let path = PathBuf::from("tests/fixtures/AudioCodec.inf");
+    let bytes = std::fs::read(&path).unwrap();
+    let mut inf_file = WinInfFile::default();
+    inf_file.parse(path).unwrap();
+
+    // UTF-16LE offsets count the BOM and two bytes per character
+    let version = inf_file.section("Version").unwrap();
+    let span = version.span().unwrap();
+    assert_eq!((span.start.line, span.start.column), (16, 1));
+    assert_eq!(
+        &bytes[span.start.offset..span.end.offset],
+        b"[\0V\0e\0r\0s\0i\0o\0n\0]\0"
+    );
+    let entry_span = version.entry_span(0).unwrap();
+    assert_eq!(entry_span.start.line, 17);
+    assert_eq!(&bytes[entry_span.start.offset..][..2], b"S\0");
+
+    let mut inf_file = WinInfFile::default();
+    inf_file
+        .parse_str("[Reg]\nHKR,,A,0,\\\n    \"B\"\nHKR,,C\n")
+        .unwrap();
+    let reg = inf_file.section("Reg").unwrap();
+    let continued = reg.entry_span(0).unwrap();
+    assert_eq!((continued.start.line, continued.end.line), (2, 3));
+    assert_eq!(continued.end.column, 8);
+    assert_eq!(reg.entry_span(1).unwrap().start.offset, 25);
+
+    // spans follow the entries they belong to through the section methods
+    let mut reg = reg.clone();
+    let second = reg.entry_span(1);
+    reg.insert_entry(0, InfEntry::OnlyValue(InfValue::Raw("HKR,,D".to_string())));
+    assert_eq!(reg.entry_span(0), None);
+    assert_eq!(reg.entry_span(2), second);
+    reg.remove_entry(1);
+    assert_eq!(reg.entry_span(1), second);
+    reg.extend([InfEntry::OnlyValue(InfValue::Raw("HKR,,E".to_string()))]);
+    assert_eq!(reg.entries().len(), 3);
+    assert_eq!(reg.entry_span(2), None);
+
+    let mut inf_file = WinInfFile::default();
+    let err = inf_file
+        .parse_str("[Strings]\r\nA = 1\r\n  B = \"open\r\n")
+        .unwrap_err();
+    let span = err.span().unwrap();
+    assert_eq!(
+        span.start,
+        Position {
+            line: 3,
+            column: 3,
+            offset: 20,
+        }
+    );
+}
+
+#[test]
+fn test_recovering_parse() {
+    let content = concat!(
+        "[Version]\r\n",
+        "Signature=\"$Windows NT$\"\r\n",
+        "Class=\"Net\"work\r\n",
+        "[Strings]\r\n",
+        "A = 1\rB = 2\r\n",
+        "C = \"unterminated\r\n",
+    );
+
+    let mut strict = WinInfFile::default();
+    assert!(strict.parse_str(content).is_err());
+
+    let mut inf_file = WinInfFile::recovering();
+    inf_file.parse_str(content).unwrap();
+    let strings = inf_file.section("Strings").unwrap();
+    assert_eq!(strings.entries().len(), 3);
+    assert_eq!(strings.get("C").unwrap().fields(), ["unterminated"]);
+    assert!(inf_file.section("Version").unwrap().get("Class").is_none());
+
+    let diagnostics: Vec<_> = inf_file
+        .diagnostics()
+        .iter()
+        .map(|d| (d.severity, d.code, d.span.unwrap().start.line))
+        .collect();
+    assert_eq!(
+        diagnostics,
+        [
+            (Severity::Error, "text-after-quote", 3),
+            (Severity::Error, "invalid-line-ending", 5),
+            (Severity::Error, "unterminated-quote", 7),
+        ]
+    );
+    assert!(inf_file.has_errors());
+    assert!(
+        inf_file.diagnostics()[1]
+            .to_string()
+            .starts_with("5:6: error[invalid-line-ending]")
+    );
+}
+
+#[test]
+fn test_lint_rules() {
+    let mut inf_file = WinInfFile::default();
+    inf_file
+        .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+        .unwrap();
+    assert!(inf_file.lint(ComplianceLevel::Declarative).is_empty());
+
+    let mut inf_file = WinInfFile::default();
+    inf_file
+        .parse_str(concat!(
+            "[Version]\r\n",
+            "PnpLockdown = 1\r\n",
+            "CatalogFile = audio.cat\r\n",
+            "[DefaultInstall.NTamd64]\r\n",
+            "CopyFiles = Audio.Copy\r\n",
+            "[Audio.Copy]\r\n",
+            "%Extra.File%\r\n",
+            "[DestinationDirs]\r\n",
+            "Audio.Copy = 11\r\n",
+            "[Unused]\r\n",
+        ))
+        .unwrap();
+    let mut linter = Linter::new(ComplianceLevel::Declarative);
+    let diagnostics = linter.run(&inf_file);
+    let found: Vec<_> = diagnostics.iter().map(|d| (d.severity, d.code)).collect();
+    assert_eq!(
+        found,
+        [
+            (Severity::Error, "copyfiles-system32"),
+            (Severity::Error, "undefined-string"),
+            (Severity::Warning, "unreferenced-section"),
+        ]
+    );
+    assert!(diagnostics.iter().all(|d| d.span.is_some()));
+
+    linter.disable("undefined-string");
+    linter.disable("unreferenced-section");
+    assert_eq!(linter.run(&inf_file).len(), 1);
+    assert!(
+        linter
+            .rules()
+            .any(|rule| rule.code() == "disallowed-dirid" && !rule.description().is_empty())
+    );
+}
+
+#[test]
+fn test_compliance_check() {
+    let mut inf_file = WinInfFile::default();
+    inf_file
+        .parse(PathBuf::from("tests/fixtures/sampledisplay.inf"))
+        .unwrap();
+    let report = inf_file.check_compliance(ComplianceLevel::Declarative);
+    assert_eq!(report.classification, ComplianceLevel::Declarative);
+    assert!(report.is_compliant());
+
+    let mut inf_file = WinInfFile::default();
+    inf_file
+        .parse_str(concat!(
+            "[Inst.NT]\n",
+            "CopyFiles = Inst.Copy\n",
+            "AddReg = Inst.Reg\n",
+            "LogConfig = Inst.LC\n",
+            "[Inst.NT.CoInstallers]\n",
+            "AddReg = CoInst.Reg\n",
+            "[Inst.Reg]\n",
+            "HKR,,Setting,0x10001,1\n",
+            "HKLM,Software\\Contoso,Path,,%13%\n",
+            "[CoInst.Reg]\n",
+            "HKCR,CLSID,,,x\n",
+            "[DestinationDirs]\n",
+            "Inst.Copy = 11\n",
+        ))
+        .unwrap();
+
+    let found = |level| {
+        inf_file
+            .check_compliance(level)
+            .violations
+            .into_iter()
+            .map(|v| (v.code, v.section, v.span.unwrap().start.line))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        found(ComplianceLevel::Universal),
+        [
+            ("disallowed-directive", "Inst.NT".to_string(), 4),
+            ("disallowed-section", "Inst.NT.CoInstallers".to_string(), 5),
+            ("disallowed-registry-root", "CoInst.Reg".to_string(), 11),
+        ]
+    );
+    assert_eq!(
+        found(ComplianceLevel::Declarative),
+        [
+            ("copyfiles-system32", "Inst.NT".to_string(), 2),
+            ("disallowed-directive", "Inst.NT".to_string(), 4),
+            ("disallowed-section", "Inst.NT.CoInstallers".to_string(), 5),
+            ("disallowed-registry-root", "Inst.Reg".to_string(), 9),
+            ("disallowed-registry-root", "CoInst.Reg".to_string(), 11),
+        ]
+    );
+    let report = inf_file.check_compliance(ComplianceLevel::Legacy);
+    assert_eq!(report.classification, ComplianceLevel::Legacy);
+    assert!(report.is_compliant());
+}
+
+#[test]
+fn test_section_graph() {
+    let mut inf_file = WinInfFile::default();
+    inf_file
+        .parse(PathBuf::from("tests/fixtures/AudioCodec.inf"))
+        .unwrap();
+    let graph = inf_file.section_graph();
+
+    let models: Vec<_> = graph
+        .edges()
+        .iter()
+        .filter(|e| e.directive == "Model")
+        .map(|e| e.to.as_str())
+        .collect();
+    assert_eq!(
+        models,
+        [
+            "Audio_Device.NT",
+            "Audio_Device.NT.Services",
+            "Audio_Device.NT.Wdf",
+            "Audio_Device.NT.Interfaces"
+        ]
+    );
+    assert_eq!(graph.dangling_references().count(), 0);
+    assert!(graph.unreachable_sections().is_empty());
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph inf {\n"));
+    assert!(dot.contains("\"Audio_Device.NT.Wdf\" -> \"Audio_wdfsect\" [label=\"KmdfService\"];"));
+}
+
 fn read_key_value(entry: InfEntry) -> Option<(String, Option<InfValue>)> {
     if let InfEntry::KeyValue(key, value) = entry {
         Some((key.clone(), value.clone()))
@@ -691,3 +1668,7 @@ fn read_value_only(entry: InfEntry) -> Option<InfValue> {
         None
     }
 }
+
+fn comma_separated(fields: &[&str]) -> InfValue {
+    InfValue::CommaSeparated(fields.iter().map(|f| f.to_string()).collect())
+}